@@ -0,0 +1,8519 @@
+use bevy::asset::{LoadState, RenderAssetUsages};
+use bevy::camera::visibility::NoFrustumCulling;
+use bevy::ecs::system::SystemParam;
+use bevy::input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::post_process::bloom::Bloom;
+use bevy::render::view::Hdr;
+use bevy::ui::FocusPolicy;
+use bevy::window::{PresentMode, WindowCloseRequested, WindowFocused};
+use bevy::{input::mouse::MouseMotion, prelude::*, window::WindowResized};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, f32::consts::PI, fmt::Write, time::Duration};
+
+const MAIN_FONT_PATH: &str = "Doto_Rounded-Bold.ttf";
+const PRELOADED_SOUNDS: [&str; 3] = ["Boom29.wav", "Ball_Flick.wav", "Random32.wav"];
+#[cfg(not(test))]
+const GHOST_FILE_PATH: &str = "best_run.ghost";
+#[cfg(not(test))]
+const CONFIG_FILE_PATH: &str = "config.ron";
+// the tests play whole runs, which must not overwrite the player's own files
+#[cfg(test)]
+const GHOST_FILE_PATH: &str = "target/test_best_run.ghost";
+#[cfg(test)]
+const CONFIG_FILE_PATH: &str = "target/test_config.ron";
+const CONFIG_VERSION: u32 = 1;
+const SEED_MAX_DIGITS: usize = 10;
+const GHOST_ALPHA: f32 = 0.25;
+const PLAYER_MOVEMENT_SPEED_NORMALIZED: f32 = 0.5; // how much of the entire screen should the player travel per second
+const BULLET_MOVEMENT_SPEED_NORMALIZED: f32 = 0.4;
+const BULLET_MAX_SPEED_MULTIPLIER: f32 = 2.5; // cap for bounce speedup, keeps bullets from tunneling through each other
+const BOUNCE_ELASTICITY_LOW: f32 = 1.02;
+const BOUNCE_ELASTICITY_HIGH: f32 = 1.05;
+const BULLET_COLOR_OSCILATION_SPEED: f32 = 108.;
+const REDUCED_MOTION_BULLET_HUE: f32 = 190.; // a calm cyan that stays readable against the dark background
+const BULLET_STATIC_HUES: [f32; 5] = [REDUCED_MOTION_BULLET_HUE, 0., 50., 120., 290.]; // fixed colors the settings menu cycles through
+const REDUCED_MOTION_TRAIL_FADE_RATE: f32 = 0.5; // trails fade at this fraction of the normal speed
+const BULLET_GLOW_INTENSITY: f32 = 3.0; // pushes bullet colors past 1.0 so the bloom pass picks them up
+const BLOOM_INTENSITY: f32 = 0.25;
+const BULLET_PARTICLE_INTERVAL: f32 = 0.1; // trail preset for medium particle quality
+const TRAIL_PARTICLE_LIFETIME: f32 = 0.7; // trail preset for medium particle quality
+const COLLISION_PARTICLE_LIFETIME: f32 = 0.5;
+const COLLISION_PARTICLE_COUNT: i32 = 32; // per bounce, on medium particle quality
+const COLLISION_PARTICLE_SPEED_NORMALIZED: f32 = 0.3;
+const WALL_RIPPLE_LIFETIME: f32 = 0.3;
+const WALL_RIPPLE_DOT_COUNT: usize = 5; // per wall bounce, on medium particle quality
+const WALL_RIPPLE_SPREAD: f32 = 0.05; // how far along the wall the ripple reaches, relative to the shorter screen dimension
+const WALL_RIPPLE_BULGE: f32 = 0.3; // how far the middle of the arc leans into the arena, relative to its spread
+const WALL_RIPPLE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.6);
+const PARTICLE_MESH_RESOLUTION: usize = 32; // same vertex count as the circle mesh the bullets use
+const SCREENSHAKE_VELOCITY: f32 = 213.7;
+const SCREENSHAKE_ON_SHOOT: f32 = 0.005;
+const SCREENSHAKE_ON_BOUNCE: f32 = 0.003;
+const SCREENSHAKE_ON_DEATH: f32 = 0.01;
+const SCREENSHAKE_DAMPENING: f32 = 10.0;
+const ZOOM_PUNCH_ON_SHOOT: f32 = 0.005; // how much the camera view shrinks, as a fraction of the normal scale
+const ZOOM_PUNCH_ON_BOUNCE: f32 = 0.015;
+const ZOOM_PUNCH_ON_DEATH: f32 = 0.08;
+const ZOOM_PUNCH_MAX: f32 = 0.2;
+const ZOOM_PUNCH_DAMPENING: f32 = 8.0;
+const CAMERA_FOLLOW_STEPS: [f32; 4] = [0.0, 0.25, 0.5, 1.0]; // 0 keeps the camera still
+const CAMERA_FOLLOW_MAX_OFFSET: f32 = 0.05; // furthest drift at full strength, relative to the shorter screen dimension
+const CAMERA_FOLLOW_RATE: f32 = 2.0; // how quickly the camera eases toward the action
+const DEATH_CINEMATIC_DURATION: f32 = 0.6; // real seconds between the hit and the game over screen
+const DEATH_CINEMATIC_ZOOM: f32 = 0.6; // camera scale the death framing eases into
+const SCREEN_FLASH_ON_DEATH: f32 = 0.6; // peak opacity of the full screen flash
+const SCREEN_FLASH_ON_BOMB: f32 = 0.3;
+const SCREENSHAKE_ON_BOMB: f32 = 0.008;
+const BOMB_CHARGES: u32 = 3; // granted at the start of every run
+const ASSIST_HITBOX_FACTOR: f32 = 0.75; // the player's hit circle shrinks to this in assist mode
+const ASSIST_BULLET_SPEED_FACTOR: f32 = 0.8;
+const ASSIST_GRACE_PERIOD: f32 = 3.0; // seconds at the start of an assisted run in which nothing can hit the player
+const REVIVE_RECOVERY_DURATION: f32 = 2.0; // invulnerability after a revive
+const REVIVE_CLEAR_RADIUS: f32 = 0.15; // bullets this close, relative to the shorter screen dimension, get sent away on a revive
+const RECOVERY_FLASH_RATE: f32 = 10.0; // blinks per second of an invulnerable player
+const BOMB_FREEZE_DURATION: f32 = 1.5;
+const SCREEN_FLASH_DAMPENING: f32 = 6.0;
+const PLAYER_SIZE: f32 = 0.02;
+const MIN_ARENA_SIZE: f32 = 100.0; // in pixels, tiny or minimized windows still get an arena everything fits into
+const BULLET_SPAWN_OFFSET: f32 = 3.0; // distance from the player center to a fresh bullet, in player sizes
+const BULLET_SPAWN_CLEARANCE: f32 = 0.5; // player sizes always kept between a fresh bullet and the death radius
+const BULLET_FADE_IN_DURATION: f32 = 0.1;
+// z layers, live bullets always draw above their own trails and the player above everything in the arena
+const HEATMAP_LAYER: f32 = -4.0;
+const SPEED_LINE_LAYER: f32 = -3.5; // under everything that moves, so the lines never hide a bullet
+const TRAIL_LAYER: f32 = -3.0;
+const BOUNCE_PARTICLE_LAYER: f32 = -2.0;
+const GHOST_LAYER: f32 = -1.0;
+const MENU_BACKGROUND_LAYER: f32 = -1.0; // never on screen together with the ghost
+const BULLET_LAYER: f32 = 0.0;
+const PLAYER_LAYER: f32 = 1.0;
+const AIM_LAYER: f32 = 2.0;
+const THREAT_INDICATOR_LAYER: f32 = 3.0;
+const PLAYER_RING_THICKNESS: f32 = 0.3; // fraction of the player radius taken up by the outline
+const PLAYER_CORE_SIZE: f32 = 0.4; // fraction of the player radius taken up by the filled center
+const PRACTICE_PLAYER_ALPHA: f32 = 0.4;
+const PLAYER_TRAIL_COLOR: Color = Color::srgba(0.6, 0.85, 1.0, 0.35); // faint and cold, so it never reads as a bullet trail
+const PLAYER_IDLE_PULSE_SCALE: f32 = 0.06; // how much the player grows at the peak of a breath
+const PLAYER_IDLE_PULSE_SPEED: f32 = 3.0;
+const FOCUS_SPEED_MULTIPLIER: f32 = 0.5; // movement speed while the focus key is held
+const HITBOX_INDICATOR_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+const HITBOX_SCALE_DEFAULT: f32 = 0.6; // the player's hit circle is smaller than its sprite, like in most bullet hell games
+const RUMBLE_STRENGTH_STEPS: [f32; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+const SCREENSHAKE_SCALE_STEPS: [f32; 5] = [0.0, 0.5, 1.0, 1.5, 2.0];
+const RETICLE_SIZE_STEPS: [f32; 4] = [0.5, 0.75, 1.0, 1.5];
+const FONT_SCALE_STEPS: [f32; 4] = [1.0, 1.25, 1.5, 2.0];
+const BRIGHTNESS_STEPS: [f32; 4] = [1.0, 1.2, 1.4, 1.6];
+const BRIGHTNESS_BACKGROUND_LIFT: f32 = 0.05; // how much the background value rises per whole step of brightness over 1
+const RETICLE_CROSSHAIR_THICKNESS: f32 = 0.35; // fraction of the reticle radius
+const PLAYER_SPEED_SCALE_STEPS: [f32; 5] = [0.8, 0.9, 1.0, 1.1, 1.2];
+const HITBOX_SCALE_STEPS: [f32; 4] = [0.4, 0.6, 0.8, 1.0];
+const ARENA_FRACTION_STEPS: [f32; 6] = [0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+const GAMEPAD_STICK_DEADZONE: f32 = 0.1;
+const KEYBOARD_AIM_SPEED: f32 = 0.8; // how much of the entire screen the reticle travels per second
+const GAMEPAD_AIM_DEADZONE: f32 = 0.5;
+const GAMEPAD_FIRE_TRIGGER_THRESHOLD: f32 = 0.3; // how far an analog fire trigger has to be pulled, well past resting noise
+const GAMEPAD_AIM_DISTANCE: f32 = 0.1;
+const GAMEPAD_AIM_DISTANCE_FAR: f32 = 0.2;
+const GAMEPAD_AIM_MIN_REACH: f32 = 0.3; // fraction of the aim distance used by a stick pushed just past the deadzone
+const AIM_ASSIST_MAX_BIAS: f32 = 0.35; // how far toward the nearest bullet a full strength assist pulls the stick direction
+const AIM_ASSIST_STEP: f32 = 0.5;
+const MOUSE_DEADZONE: f32 = 1.0; // in pixels per motion message
+const WAVE_INTERVAL_START: f32 = 6.0; // seconds between edge waves at the start of a run
+const WAVE_INTERVAL_MIN: f32 = 2.0;
+const WAVE_INTERVAL_RAMP: f32 = 90.0; // seconds of survival it takes to reach the minimum wave interval
+const WAVE_MAX_BULLETS: usize = 5;
+const SHOT_MAX_PELLETS: u32 = 7;
+const SHOT_SPREAD_COUNT: u32 = 3; // the spread preset picked from the settings menu
+const SHOT_SPREAD_ANGLE: f32 = 30.0; // degrees between the outermost pellets
+const SHOT_SPREAD_JITTER: f32 = 0.15; // fraction of the gap between pellets each one may stray
+const WAVE_BULLET_GROWTH: f32 = 20.0; // seconds of survival per extra bullet in a wave
+const WAVE_WARNING_DURATION: f32 = 0.8;
+const SURVIVAL_FIRST_WAVE_DELAY: f32 = 1.5; // without the player's own bullets, waiting for the regular first wave is just dead time
+const HEATMAP_STAMP_INTERVAL: f32 = 0.25; // seconds between two stamps of every bullet position
+const HEATMAP_STAMP_ALPHA: f32 = 0.03; // low enough that only areas bullets keep returning to build up
+const HEATMAP_STAMP_SCALE: f32 = 2.0; // relative to the bullet size
+const HEATMAP_MAX_STAMPS: usize = 1500; // past this the oldest stamp is moved instead of spawning another
+const THREAT_INDICATOR_COUNT: usize = 3; // how many of the closest bullets get an arrow
+const THREAT_INDICATOR_RANGE: f32 = 0.5; // bullets further than this, relative to the shorter screen dimension, get no arrow
+const THREAT_INDICATOR_SIZE: f32 = 0.015;
+const VELOCITY_VECTOR_SECONDS: f32 = 0.25; // each line reaches where its bullet will be this far ahead
+const VELOCITY_VECTOR_COLOR: Color = Color::srgb(0.3, 1.0, 0.4); // stays clear of the bullet hues and the dark background
+const THREAT_INDICATOR_MARGIN: f32 = 0.03; // distance kept from the screen edge
+const SPEED_LINE_COUNT: usize = 24;
+const SPEED_LINE_LENGTH: f32 = 0.12; // relative to the shorter screen dimension
+const SPEED_LINE_THICKNESS: f32 = 0.002;
+const SPEED_LINE_TRAVEL: f32 = 0.25; // how far a line slides in from the edge before looping back
+const SPEED_LINE_SPEED: f32 = 1.5; // loops per real second
+const SPEED_LINE_MAX_ALPHA: f32 = 0.2;
+const DANGER_LOOKAHEAD: f32 = 0.5; // seconds of straight-line travel checked against the player
+const DANGER_CHECK_RADIUS: f32 = 0.4; // bullets further away than this, relative to the shorter screen dimension, are skipped
+const DANGER_PULSE_SCALE: f32 = 0.35;
+const DANGER_PULSE_SPEED: f32 = 18.0;
+const MENU_BACKGROUND_BALL_COUNT: usize = 6;
+const MENU_BACKGROUND_BALL_SPEED: f32 = 0.3; // fraction of the regular bullet speed
+const MENU_REPEAT_INITIAL_DELAY: f32 = 0.4; // how long a direction has to be held before the selection starts scrolling
+const MENU_REPEAT_INTERVAL: f32 = 0.12;
+const PAUSE_TOGGLE_COOLDOWN: f32 = 0.15; // real time seconds during which further pause presses are ignored
+const TIMED_MODE_DURATION: f32 = 60.0;
+const SCORE_ATTACK_POINTS_PER_SECOND: f32 = 1.0; // a trickle for just staying alive
+const SCORE_ATTACK_POINTS_PER_BOUNCE: f32 = 1.0; // per bullet on screen, so crowded bounces pay the most
+const SESSION_STREAK_SURVIVAL: f32 = 30.0; // a run has to last this long to keep the session streak going
+const MAX_RUN_STEPS: [f32; 4] = [0.0, 120.0, 300.0, 600.0]; // in seconds, 0 leaves runs unlimited
+const INTENSITY_RAMP_DURATION: f32 = 10.0; // seconds of survival until the intensity peaks
+const INTENSITY_RAMP_EXPONENT: f32 = 2.0;
+const INTENSITY_SPEED_BONUS: f32 = 0.2; // bullets are this much faster at full intensity
+const SUDDEN_DEATH_STEPS: [f32; 5] = [0.0, 60.0, 90.0, 120.0, 180.0]; // in seconds, 0 turns sudden death off
+const SUDDEN_DEATH_RAMP_DURATION: f32 = 15.0; // seconds past the threshold until sudden death is at full strength
+const SUDDEN_DEATH_SPEED_BONUS: f32 = 1.0; // bullets are this much faster at full sudden death, on top of the intensity
+const SUDDEN_DEATH_FIRE_RATE_BONUS: f32 = 3.0; // the player fires this much more often at full sudden death
+const SUDDEN_DEATH_WARNING_DURATION: f32 = 3.0; // seconds the warning stays up once sudden death begins
+const SUDDEN_DEATH_WARNING_COLOR: Color = Color::srgb(1.0, 0.25, 0.2);
+const INTENSITY_SIZE_BONUS: f32 = 0.3; // with growing bullets on, they are this much larger at full intensity
+const ENDLESS_SLOWEST_FIRE_INTERVAL: f32 = 2.0;
+const TIMED_SLOWEST_FIRE_INTERVAL: f32 = 1.2; // tuned so that the full minute is survivable, but only just
+const TAP_FIRE_MIN_INTERVAL: f32 = 0.15; // fastest a tapped shot can follow the previous one
+const SCORE_CHARACTER_WIDTH: f32 = 0.65; // generous glyph advance of the main font, as a fraction of its size
+const PROGRESS_BAR_HEIGHT: f32 = 4.0; // in pixels, thin enough to stay clear of the score text below
+const PROGRESS_BAR_COLOR: Color = Color::hsv(0.0, 0.0, 0.3);
+const PROGRESS_BAR_BEATEN_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+const MENU_BACKGROUND_TINT: Color = Color::srgb(0.01, 0.012, 0.035);
+const GAME_BACKGROUND_TINT: Color = Color::srgb(0.005, 0.005, 0.01); // kept near black so the bullets stay high contrast
+const GAME_OVER_BACKGROUND_TINT: Color = Color::srgb(0.06, 0.0, 0.005);
+const BACKGROUND_TINT_RATE: f32 = 2.0; // how quickly the background fades toward the tint of a new state
+const LETTERBOX_COLOR: Color = Color::srgb(0.04, 0.04, 0.04); // just bright enough to show where the arena ends
+const TEXT_COLOR: Color = Color::hsv(0.0, 0.0, 0.5);
+const IDLE_BUTTON: Color = Color::hsv(0.0, 0.0, 1.0);
+const HOVERED_BUTTON: Color = Color::hsv(0.0, 0.0, 0.2);
+const PRESSED_BUTTON: Color = Color::hsv(0.0, 0.0, 0.6);
+const DISABLED_BUTTON: Color = Color::hsv(0.0, 0.0, 0.35);
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+enum AppState {
+    #[default]
+    Uninitialized,
+    Loading,
+    Menu,
+    InGame,
+    Paused,
+    GameOver,
+    Victory,
+    Settings,
+    Data,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+enum GameMode {
+    #[default]
+    Endless,
+    Timed,
+    // the player can't shoot, every bullet comes from the edge waves
+    Survival,
+    // scored in points earned from bullet bounces instead of survival time
+    ScoreAttack,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+enum ControlDevice {
+    Keyboard,
+    Gamepad,
+    #[default]
+    Mouse,
+}
+
+#[derive(Resource)]
+struct RandomSource(ChaCha8Rng);
+
+// the seed RandomSource was reset to at the start of the current or last run
+#[derive(Resource)]
+struct CurrentSeed {
+    value: u64,
+}
+
+// a seed typed in or copied on the main menu, none picks a fresh one every run
+#[derive(Resource)]
+struct SeedEntry {
+    value: Option<u64>,
+}
+
+#[derive(Resource)]
+struct Score {
+    value: f32,
+}
+
+// only counts up in score attack, the survival time in Score keeps driving everything else
+#[derive(Resource)]
+struct ScoreAttack {
+    points: f32,
+}
+
+// destructive buttons on the data page only act on a second press, any other press disarms them
+#[derive(Resource, Default)]
+struct DataConfirm {
+    armed: Option<DataAction>,
+}
+
+// set by the pause menu, passes straight through the main menu so the regular cleanup and setup run
+#[derive(Resource)]
+struct QuickRestart {
+    value: bool,
+}
+
+// kept around for the whole run of the app, so nothing gets unloaded and has to pop in again
+#[derive(Resource)]
+struct PreloadedAssets {
+    handles: Vec<UntypedHandle>,
+}
+
+// the font every screen uses, swapped for the built-in one if the configured file doesn't load
+#[derive(Resource)]
+struct UiFont {
+    handle: Handle<Font>,
+}
+
+// scales the value of the interface grays and lifts the background, for displays that crush the darks
+#[derive(Resource)]
+struct Brightness {
+    value: f32,
+}
+
+// the path is only set through the config file, the menu just cycles the scale
+#[derive(Resource)]
+struct FontSettings {
+    path: String,
+    scale: f32,
+}
+
+// persisted data that changed since it was last written to disk
+#[derive(Resource, Default)]
+struct PendingSaves {
+    config: bool,
+}
+
+// drives the pacing of a run from survival time alone, so score bonuses never speed it up
+#[derive(Resource)]
+struct Intensity {
+    survival_time: f32,
+    value: f32,
+    sudden_death: f32, // 0 until the sudden death threshold, then ramps up to 1
+}
+
+// maps survival time onto an intensity between 0 and 1
+#[derive(Resource)]
+struct IntensityCurve {
+    ramp_duration: f32,
+    exponent: f32,
+}
+
+impl IntensityCurve {
+    fn sample(&self, survival_time: f32) -> f32 {
+        (survival_time / self.ramp_duration)
+            .clamp(0.0, 1.0)
+            .powf(self.exponent)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+enum ParticleQuality {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ParticleQuality {
+    fn collision_particle_count(&self) -> i32 {
+        match self {
+            ParticleQuality::Low => COLLISION_PARTICLE_COUNT / 2,
+            ParticleQuality::Medium => COLLISION_PARTICLE_COUNT,
+            ParticleQuality::High => COLLISION_PARTICLE_COUNT * 3 / 2,
+        }
+    }
+
+    fn wall_ripple_dot_count(&self) -> usize {
+        match self {
+            ParticleQuality::Low => WALL_RIPPLE_DOT_COUNT - 2,
+            ParticleQuality::Medium => WALL_RIPPLE_DOT_COUNT,
+            ParticleQuality::High => WALL_RIPPLE_DOT_COUNT * 2 - 1,
+        }
+    }
+
+    // low quality gets short punchy trails, high quality long flowing ones
+    fn trail_settings(&self) -> TrailSettings {
+        match self {
+            ParticleQuality::Low => TrailSettings {
+                lifetime: TRAIL_PARTICLE_LIFETIME * 0.5,
+                interval: BULLET_PARTICLE_INTERVAL * 2.0,
+            },
+            ParticleQuality::Medium => TrailSettings {
+                lifetime: TRAIL_PARTICLE_LIFETIME,
+                interval: BULLET_PARTICLE_INTERVAL,
+            },
+            ParticleQuality::High => TrailSettings {
+                lifetime: TRAIL_PARTICLE_LIFETIME * 1.5,
+                interval: BULLET_PARTICLE_INTERVAL * 0.7,
+            },
+        }
+    }
+}
+
+#[derive(Resource)]
+struct SelectedGameMode {
+    value: GameMode,
+}
+
+#[derive(Resource)]
+struct ParticleQualitySetting {
+    value: ParticleQuality,
+}
+
+// derived from the particle quality, only picked up by bullets and particles spawned afterwards
+#[derive(Resource)]
+struct TrailSettings {
+    lifetime: f32,
+    interval: f32,
+}
+
+// single switch for motion sensitive players, suppresses every shake, flash and flicker effect
+#[derive(Resource)]
+struct ReduceMotion {
+    value: bool,
+}
+
+#[derive(Resource)]
+struct BloomEnabled {
+    value: bool,
+}
+
+// velocity multiplier applied on every bounce, 1.0 keeps bullet speed constant
+#[derive(Resource)]
+struct BounceElasticity {
+    value: f32,
+}
+
+#[derive(Resource)]
+struct WavesEnabled {
+    value: bool,
+}
+
+// scales every rumble request, 0 turns rumble off entirely
+#[derive(Resource)]
+struct RumbleStrength {
+    value: f32,
+}
+
+// the single way to shake the active gamepad, so the strength setting applies everywhere
+#[derive(SystemParam)]
+struct Rumble<'w> {
+    active_gamepad: Res<'w, ActiveGamepad>,
+    strength: Res<'w, RumbleStrength>,
+    writer: MessageWriter<'w, GamepadRumbleRequest>,
+}
+
+impl Rumble<'_> {
+    fn add(&mut self, duration_millis: u64, strong_motor: f32, weak_motor: f32) {
+        let Some(gamepad) = self.active_gamepad.value else {
+            return;
+        };
+        if self.strength.value <= 0.0 {
+            return;
+        }
+
+        self.writer.write(GamepadRumbleRequest::Add {
+            gamepad,
+            duration: Duration::from_millis(duration_millis),
+            intensity: GamepadRumbleIntensity {
+                strong_motor: strong_motor * self.strength.value,
+                weak_motor: weak_motor * self.strength.value,
+            },
+        });
+    }
+}
+
+// ranked runs since the app was started, never saved so every session starts from nothing
+#[derive(Resource, Default)]
+struct SessionStats {
+    runs_played: u32,
+    best_this_session: f32,
+    current_streak: u32,
+    longest_streak: u32,
+}
+
+// set once the controls overlay has been shown, so it only ever greets a first launch
+#[derive(Resource)]
+struct TutorialSeen {
+    value: bool,
+}
+
+// the controls overlay over the main menu, the menu ignores input while it's up
+#[derive(Component)]
+struct TutorialOverlay;
+
+// whether the session stats are shown on the main menu and the game over screen
+#[derive(Resource)]
+struct SessionStatsEnabled {
+    value: bool,
+}
+
+// the smallest unit the run clock shows, centiseconds unless a speedrunner asks for more
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum TimerPrecision {
+    #[default]
+    Centi,
+    Milli,
+}
+
+impl TimerPrecision {
+    // the widest text the clock shows below an hour, used to size its display
+    fn placeholder(&self) -> &'static str {
+        match self {
+            TimerPrecision::Centi => "00:00:00",
+            TimerPrecision::Milli => "00:00:000",
+        }
+    }
+}
+
+#[derive(Resource)]
+struct TimerPrecisionSetting {
+    value: TimerPrecision,
+}
+
+// soft cap on the length of a single run, mostly for demo and streaming setups, 0 means unlimited
+#[derive(Resource)]
+struct MaxRunSeconds {
+    value: f32,
+}
+
+// auto fires on its own timer, manual only lets the timer run while the fire input is held
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum FireMode {
+    #[default]
+    Auto,
+    Manual,
+}
+
+#[derive(Resource)]
+struct FireModeSetting {
+    value: FireMode,
+}
+
+// every fire press fires right away instead of waiting for the timer, as long as the last shot isn't too recent
+#[derive(Resource)]
+struct TapFireEnabled {
+    value: bool,
+}
+
+// the persisted form of the keyboard fire binding, only the keys the settings menu can cycle through
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum FireKey {
+    // Shift used to be offered here, it's the focus key now
+    #[default]
+    #[serde(alias = "ShiftLeft")]
+    Space,
+    // J used to be offered here, it's one of the keyboard aim keys now
+    #[serde(alias = "KeyJ")]
+    KeyF,
+    KeyX,
+}
+
+impl FireKey {
+    fn label(&self) -> &'static str {
+        match self {
+            FireKey::Space => "Space",
+            FireKey::KeyF => "F",
+            FireKey::KeyX => "X",
+        }
+    }
+}
+
+impl From<KeyCode> for FireKey {
+    fn from(key: KeyCode) -> Self {
+        match key {
+            KeyCode::KeyF => FireKey::KeyF,
+            KeyCode::KeyX => FireKey::KeyX,
+            _ => FireKey::Space,
+        }
+    }
+}
+
+impl From<FireKey> for KeyCode {
+    fn from(key: FireKey) -> Self {
+        match key {
+            FireKey::Space => KeyCode::Space,
+            FireKey::KeyF => KeyCode::KeyF,
+            FireKey::KeyX => KeyCode::KeyX,
+        }
+    }
+}
+
+// the persisted form of the gamepad pause binding, the guide button is a separate toggle on top of it
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum PauseButton {
+    #[default]
+    Start,
+    Select,
+    LeftThumb,
+    RightThumb,
+}
+
+impl PauseButton {
+    fn label(&self) -> &'static str {
+        match self {
+            PauseButton::Start => "Start",
+            PauseButton::Select => "Select",
+            PauseButton::LeftThumb => "L3",
+            PauseButton::RightThumb => "R3",
+        }
+    }
+}
+
+impl From<GamepadButton> for PauseButton {
+    fn from(button: GamepadButton) -> Self {
+        match button {
+            GamepadButton::Select => PauseButton::Select,
+            GamepadButton::LeftThumb => PauseButton::LeftThumb,
+            GamepadButton::RightThumb => PauseButton::RightThumb,
+            _ => PauseButton::Start,
+        }
+    }
+}
+
+impl From<PauseButton> for GamepadButton {
+    fn from(button: PauseButton) -> Self {
+        match button {
+            PauseButton::Start => GamepadButton::Start,
+            PauseButton::Select => GamepadButton::Select,
+            PauseButton::LeftThumb => GamepadButton::LeftThumb,
+            PauseButton::RightThumb => GamepadButton::RightThumb,
+        }
+    }
+}
+
+// inputs of the actions that can be rebound, every device gets its own binding
+#[derive(Resource)]
+struct KeyBindings {
+    fire_key: KeyCode,
+    fire_mouse: MouseButton,
+    fire_gamepad: GamepadButton,
+    aim_up: KeyCode,
+    aim_down: KeyCode,
+    aim_left: KeyCode,
+    aim_right: KeyCode,
+    bomb_key: KeyCode,
+    bomb_mouse: MouseButton,
+    bomb_gamepad: GamepadButton,
+    focus_key: KeyCode,
+    focus_gamepad: GamepadButton,
+    pause_gamepad: GamepadButton,
+    pause_on_guide: bool, // not every platform reports the guide button, so it's only an extra
+}
+
+impl KeyBindings {
+    fn is_pause_button(&self, button: GamepadButton) -> bool {
+        button == self.pause_gamepad || (self.pause_on_guide && button == GamepadButton::Mode)
+    }
+}
+
+// names as printed on the keyboard, anything unusual enough to have no short name keeps bevy's name
+fn key_label(key: KeyCode) -> String {
+    let label = match key {
+        KeyCode::Space => "Space",
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => "Shift",
+        KeyCode::ControlLeft | KeyCode::ControlRight => "Ctrl",
+        KeyCode::AltLeft | KeyCode::AltRight => "Alt",
+        KeyCode::Tab => "Tab",
+        KeyCode::Enter => "Enter",
+        KeyCode::Escape => "Esc",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::ArrowUp => "Up",
+        KeyCode::ArrowDown => "Down",
+        KeyCode::ArrowLeft => "Left",
+        KeyCode::ArrowRight => "Right",
+        KeyCode::KeyA => "A",
+        KeyCode::KeyB => "B",
+        KeyCode::KeyC => "C",
+        KeyCode::KeyD => "D",
+        KeyCode::KeyE => "E",
+        KeyCode::KeyF => "F",
+        KeyCode::KeyG => "G",
+        KeyCode::KeyH => "H",
+        KeyCode::KeyI => "I",
+        KeyCode::KeyJ => "J",
+        KeyCode::KeyK => "K",
+        KeyCode::KeyL => "L",
+        KeyCode::KeyM => "M",
+        KeyCode::KeyN => "N",
+        KeyCode::KeyO => "O",
+        KeyCode::KeyP => "P",
+        KeyCode::KeyQ => "Q",
+        KeyCode::KeyR => "R",
+        KeyCode::KeyS => "S",
+        KeyCode::KeyT => "T",
+        KeyCode::KeyU => "U",
+        KeyCode::KeyV => "V",
+        KeyCode::KeyW => "W",
+        KeyCode::KeyX => "X",
+        KeyCode::KeyY => "Y",
+        KeyCode::KeyZ => "Z",
+        KeyCode::Digit0 => "0",
+        KeyCode::Digit1 => "1",
+        KeyCode::Digit2 => "2",
+        KeyCode::Digit3 => "3",
+        KeyCode::Digit4 => "4",
+        KeyCode::Digit5 => "5",
+        KeyCode::Digit6 => "6",
+        KeyCode::Digit7 => "7",
+        KeyCode::Digit8 => "8",
+        KeyCode::Digit9 => "9",
+        _ => return format!("{:?}", key),
+    };
+    label.to_string()
+}
+
+fn mouse_button_label(button: MouseButton) -> String {
+    match button {
+        MouseButton::Left => "left mouse".to_string(),
+        MouseButton::Right => "right mouse".to_string(),
+        MouseButton::Middle => "middle mouse".to_string(),
+        MouseButton::Back => "mouse back".to_string(),
+        MouseButton::Forward => "mouse forward".to_string(),
+        MouseButton::Other(index) => format!("mouse {}", index),
+    }
+}
+
+// south, east, north and west go by their xbox letters, like the rest of the interface
+fn gamepad_button_label(button: GamepadButton) -> &'static str {
+    match button {
+        GamepadButton::South => "A",
+        GamepadButton::East => "B",
+        GamepadButton::North => "Y",
+        GamepadButton::West => "X",
+        GamepadButton::C => "C",
+        GamepadButton::Z => "Z",
+        GamepadButton::LeftTrigger => "left bumper",
+        GamepadButton::LeftTrigger2 => "left trigger",
+        GamepadButton::RightTrigger => "right bumper",
+        GamepadButton::RightTrigger2 => "right trigger",
+        GamepadButton::Select => "Select",
+        GamepadButton::Start => "Start",
+        GamepadButton::Mode => "Guide",
+        GamepadButton::LeftThumb => "L3",
+        GamepadButton::RightThumb => "R3",
+        GamepadButton::DPadUp => "d-pad up",
+        GamepadButton::DPadDown => "d-pad down",
+        GamepadButton::DPadLeft => "d-pad left",
+        GamepadButton::DPadRight => "d-pad right",
+        GamepadButton::Other(_) => "extra button",
+    }
+}
+
+// whether the player is currently allowed to shoot, always true outside of manual fire
+#[derive(SystemParam)]
+struct FireInput<'w, 's> {
+    fire_mode: Res<'w, FireModeSetting>,
+    tap_fire: Res<'w, TapFireEnabled>,
+    bindings: Res<'w, KeyBindings>,
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+    active_gamepad: Res<'w, ActiveGamepad>,
+}
+
+impl FireInput<'_, '_> {
+    fn held(&self) -> bool {
+        if self.fire_mode.value == FireMode::Auto {
+            return true;
+        }
+
+        self.keyboard.pressed(self.bindings.fire_key)
+            || self.mouse.pressed(self.bindings.fire_mouse)
+            || self.gamepad_held()
+    }
+
+    // the digital press of an analog trigger only comes near the end of its travel, so a light pull is read
+    // from the axis as well, only the active gamepad can fire
+    fn gamepad_held(&self) -> bool {
+        let Some(gamepad) = self
+            .active_gamepad
+            .value
+            .and_then(|entity| self.gamepads.get(entity).ok())
+        else {
+            return false;
+        };
+        gamepad.pressed(self.bindings.fire_gamepad)
+            || gamepad
+                .get(self.bindings.fire_gamepad)
+                .is_some_and(|pull| pull > GAMEPAD_FIRE_TRIGGER_THRESHOLD)
+    }
+
+    // a fresh press this frame, only reported while tap fire is on
+    fn tapped(&self) -> bool {
+        if !self.tap_fire.value {
+            return false;
+        }
+
+        self.keyboard.just_pressed(self.bindings.fire_key)
+            || self.mouse.just_pressed(self.bindings.fire_mouse)
+            || self
+                .active_gamepad
+                .value
+                .and_then(|entity| self.gamepads.get(entity).ok())
+                .is_some_and(|gamepad| gamepad.just_pressed(self.bindings.fire_gamepad))
+    }
+}
+
+// panic button charges of the current run, using one freezes every bullet for a moment
+#[derive(Resource)]
+struct Bombs {
+    charges: u32,
+    freeze_remaining: f32,
+}
+
+// a subtle background tint per state, pure black throughout when off
+#[derive(Resource)]
+struct BackgroundTintEnabled {
+    value: bool,
+}
+
+// pauses the run when the controller playing it goes away, so a dead battery doesn't end it
+#[derive(Resource)]
+struct PauseOnDisconnect {
+    value: bool,
+}
+
+// set when the run was paused by a disconnect, so the pause screen can explain why
+#[derive(Resource, Default)]
+struct ControllerDisconnected {
+    value: bool,
+}
+
+// the line on the pause screen saying the controller went away
+#[derive(Component)]
+struct DisconnectNotice;
+
+// eases the camera onto the player after a hit, off by default since it delays the game over screen
+#[derive(Resource)]
+struct DeathCinematicEnabled {
+    value: bool,
+}
+
+// bullets leave a smooth tapering streak instead of a line of dots
+#[derive(Resource)]
+struct RibbonTrailsEnabled {
+    value: bool,
+}
+
+// faint particles left behind a moving player, off by default since some find it cluttering
+#[derive(Resource)]
+struct PlayerTrailEnabled {
+    value: bool,
+}
+
+// bullets slowly grow with the intensity, classic runs keep them at a fixed size
+#[derive(Resource)]
+struct GrowingBulletsEnabled {
+    value: bool,
+}
+
+// current size of every bullet relative to its mesh, shared by the visuals and all collision checks
+#[derive(Resource)]
+struct BulletGrowth {
+    value: f32,
+}
+
+// makes bullets about to hit the player pulse
+#[derive(Resource)]
+struct DangerHighlightEnabled {
+    value: bool,
+}
+
+// multiplies the player's radius for the death check only, bullets still bounce off each other at full size
+#[derive(Resource)]
+struct HitboxScale {
+    value: f32,
+}
+
+// arrows at the screen edge pointing at nearby bullets, off by default since it makes dodging easier
+#[derive(Resource)]
+struct ThreatIndicatorsEnabled {
+    value: bool,
+}
+
+// a line ahead of every bullet showing its direction and speed, a learning and debugging aid
+#[derive(Resource)]
+struct VelocityVectorsEnabled {
+    value: bool,
+}
+
+// analysis overlay, accumulates where bullets have been over the last stretch of the run
+#[derive(Resource)]
+struct HeatmapEnabled {
+    value: bool,
+}
+
+#[derive(Resource)]
+struct HeatmapStamper {
+    timer: Timer,
+    // oldest first, reused in turn once the cap is reached so a long run can't pile up entities
+    stamps: VecDeque<Entity>,
+}
+
+#[derive(Resource)]
+struct WaveSpawner {
+    timer: Timer,
+}
+
+// player positions of the current run, normalized to the shorter screen dimension, one per fixed step
+#[derive(Resource)]
+struct GhostRecorder {
+    samples: Vec<Vec2>,
+}
+
+#[derive(Resource)]
+struct BestGhost {
+    duration: f32,
+    samples: Vec<Vec2>,
+}
+
+// how far from the player the gamepad reticle sits, relative to the shorter screen dimension
+#[derive(Resource)]
+struct GamepadAimDistance {
+    value: f32,
+}
+
+// 0 leaves gamepad aiming fully manual, 1 is the strongest pull toward the nearest bullet
+#[derive(Resource)]
+struct AimAssist {
+    value: f32,
+}
+
+// relative aim moves the reticle by the mouse motion, absolute aim puts it right under the cursor
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum AimMode {
+    #[default]
+    Relative,
+    Absolute,
+}
+
+#[derive(Resource)]
+struct AimModeSetting {
+    value: AimMode,
+}
+
+// moves the reticle with the mouse every rendered frame instead of on the next fixed step
+#[derive(Resource)]
+struct FrameRateAim {
+    value: bool,
+}
+
+// a fixed hue skips the per-frame material write altogether
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum BulletColorMode {
+    #[default]
+    Oscillate,
+    Static {
+        hue: f32,
+    },
+}
+
+#[derive(Resource)]
+struct BulletColorSetting {
+    value: BulletColorMode,
+}
+
+// how many bullets a single shot fires, a spread fans them out evenly across the angle (in degrees)
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum ShotPattern {
+    #[default]
+    Single,
+    Spread {
+        count: u32,
+        angle: f32,
+    },
+}
+
+impl ShotPattern {
+    // the offset of every pellet from the aim direction, in radians, before any jitter
+    fn pellet_angles(&self) -> Vec<f32> {
+        match *self {
+            ShotPattern::Single => vec![0.0],
+            ShotPattern::Spread { count, angle } => {
+                let count = count.clamp(1, SHOT_MAX_PELLETS);
+                if count == 1 {
+                    return vec![0.0];
+                }
+                let angle = angle.to_radians();
+                (0..count)
+                    .map(|i| angle * (i as f32 / (count - 1) as f32 - 0.5))
+                    .collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum ReticleShape {
+    #[default]
+    Dot,
+    Crosshair,
+}
+
+// a hidden reticle is still spawned and moved around, the shots need its position either way
+#[derive(Resource)]
+struct ReticleSettings {
+    visible: bool,
+    shape: ReticleShape,
+    size: f32,
+}
+
+#[derive(Resource)]
+struct PresentModeSetting {
+    value: PresentMode,
+}
+
+// practice runs can't kill the player, and are therefore never considered ranked
+#[derive(Resource)]
+struct PracticeMode {
+    value: bool,
+}
+
+// a bundle of helpers for new players: a smaller hitbox, slower bullets, a grace period and one free revive
+// assisted runs are never considered ranked either
+#[derive(Resource)]
+struct AssistMode {
+    value: bool,
+}
+
+// one revive per run, off by default since it takes the edge off every run
+#[derive(Resource)]
+struct ReviveEnabled {
+    value: bool,
+}
+
+// revives left in the current run, and how long the player still can't be hit
+#[derive(Resource, Default)]
+struct Recovery {
+    revives: u32,
+    invulnerable_remaining: f32,
+}
+
+// on very wide or tall windows the arena can be limited to a fixed aspect ratio, the rest is letterboxed
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum ArenaAspect {
+    #[default]
+    Full,
+    Widescreen,
+    Standard,
+}
+
+impl ArenaAspect {
+    fn ratio(&self) -> Option<f32> {
+        match self {
+            ArenaAspect::Full => None,
+            ArenaAspect::Widescreen => Some(16.0 / 9.0),
+            ArenaAspect::Standard => Some(4.0 / 3.0),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ArenaAspectSetting {
+    value: ArenaAspect,
+}
+
+// shrinks the arena toward the center of the window, the freed space is letterboxed like the aspect limit
+#[derive(Resource)]
+struct ArenaFraction {
+    value: f32,
+}
+
+// the persisted form of PresentModeSetting, only the modes the settings menu can cycle through
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum VsyncMode {
+    #[default]
+    On,
+    Off,
+    Mailbox,
+}
+
+impl From<PresentMode> for VsyncMode {
+    fn from(mode: PresentMode) -> Self {
+        match mode {
+            PresentMode::AutoNoVsync => VsyncMode::Off,
+            PresentMode::Mailbox => VsyncMode::Mailbox,
+            _ => VsyncMode::On,
+        }
+    }
+}
+
+impl From<VsyncMode> for PresentMode {
+    fn from(mode: VsyncMode) -> Self {
+        match mode {
+            VsyncMode::On => PresentMode::AutoVsync,
+            VsyncMode::Off => PresentMode::AutoNoVsync,
+            VsyncMode::Mailbox => PresentMode::Mailbox,
+        }
+    }
+}
+
+// everything from GameSettings that survives a restart, fields missing from an older file keep their defaults
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct GameConfig {
+    version: u32,
+    game_mode: GameMode,
+    particle_quality: ParticleQuality,
+    reduce_motion: bool,
+    bloom: bool,
+    practice: bool,
+    assist: bool,
+    revive: bool,
+    vsync: VsyncMode,
+    waves: bool,
+    elasticity: f32,
+    heatmap: bool,
+    aim_distance: f32,
+    aim_assist: f32,
+    threat_indicators: bool,
+    velocity_vectors: bool,
+    hitbox_scale: f32,
+    arena_aspect: ArenaAspect,
+    arena_fraction: f32,
+    danger_highlight: bool,
+    rumble_strength: f32,
+    camera_follow: f32,
+    screenshake: ScreenshakeConfig,
+    movement: MovementConfig,
+    sudden_death: SuddenDeathConfig,
+    player_trail: bool,
+    ribbon_trails: bool,
+    growing_bullets: bool,
+    aim_mode: AimMode,
+    frame_rate_aim: bool,
+    fire_mode: FireMode,
+    tap_fire: bool,
+    fire_key: FireKey,
+    shot_pattern: ShotPattern,
+    bullet_color: BulletColorMode,
+    pause_button: PauseButton,
+    guide_pauses: bool,
+    pause_on_disconnect: bool,
+    max_run_seconds: f32,
+    timer_precision: TimerPrecision,
+    session_stats: bool,
+    seen_tutorial: bool,
+    reticle_visible: bool,
+    reticle_shape: ReticleShape,
+    reticle_size: f32,
+    background_tint: bool,
+    death_cinematic: bool,
+    font_path: String,
+    font_scale: f32,
+    brightness: f32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            version: CONFIG_VERSION,
+            game_mode: GameMode::Endless,
+            particle_quality: ParticleQuality::Medium,
+            reduce_motion: false,
+            bloom: true,
+            practice: false,
+            assist: false,
+            revive: false,
+            vsync: VsyncMode::On,
+            waves: false,
+            elasticity: 1.0,
+            heatmap: false,
+            aim_distance: GAMEPAD_AIM_DISTANCE,
+            aim_assist: 0.0,
+            threat_indicators: false,
+            velocity_vectors: false,
+            hitbox_scale: HITBOX_SCALE_DEFAULT,
+            arena_aspect: ArenaAspect::Full,
+            arena_fraction: 1.0,
+            danger_highlight: false,
+            rumble_strength: 1.0,
+            camera_follow: 0.0,
+            screenshake: ScreenshakeConfig::default(),
+            movement: MovementConfig::default(),
+            sudden_death: SuddenDeathConfig::default(),
+            player_trail: false,
+            ribbon_trails: false,
+            growing_bullets: false,
+            aim_mode: AimMode::Relative,
+            frame_rate_aim: false,
+            fire_mode: FireMode::Auto,
+            tap_fire: false,
+            fire_key: FireKey::Space,
+            shot_pattern: ShotPattern::Single,
+            bullet_color: BulletColorMode::Oscillate,
+            pause_button: PauseButton::Start,
+            guide_pauses: false,
+            pause_on_disconnect: true,
+            max_run_seconds: 0.0,
+            timer_precision: TimerPrecision::Centi,
+            session_stats: false,
+            seen_tutorial: false,
+            reticle_visible: true,
+            reticle_shape: ReticleShape::Dot,
+            reticle_size: 1.0,
+            background_tint: true,
+            death_cinematic: false,
+            font_path: MAIN_FONT_PATH.to_string(),
+            font_scale: 1.0,
+            brightness: 1.0,
+        }
+    }
+}
+
+impl GameConfig {
+    fn insert_resources(self, world: &mut World) {
+        world.insert_resource(SelectedGameMode {
+            value: self.game_mode,
+        });
+        world.insert_resource(ParticleQualitySetting {
+            value: self.particle_quality,
+        });
+        world.insert_resource(self.particle_quality.trail_settings());
+        world.insert_resource(ReduceMotion {
+            value: self.reduce_motion,
+        });
+        world.insert_resource(BloomEnabled { value: self.bloom });
+        world.insert_resource(PracticeMode {
+            value: self.practice,
+        });
+        world.insert_resource(AssistMode { value: self.assist });
+        world.insert_resource(ReviveEnabled { value: self.revive });
+        world.insert_resource(PresentModeSetting {
+            value: self.vsync.into(),
+        });
+        world.insert_resource(WavesEnabled { value: self.waves });
+        world.insert_resource(BounceElasticity {
+            value: self.elasticity,
+        });
+        world.insert_resource(HeatmapEnabled {
+            value: self.heatmap,
+        });
+        world.insert_resource(GamepadAimDistance {
+            value: self.aim_distance,
+        });
+        world.insert_resource(AimAssist {
+            value: self.aim_assist.clamp(0.0, 1.0),
+        });
+        world.insert_resource(ThreatIndicatorsEnabled {
+            value: self.threat_indicators,
+        });
+        world.insert_resource(VelocityVectorsEnabled {
+            value: self.velocity_vectors,
+        });
+        world.insert_resource(HitboxScale {
+            value: self.hitbox_scale.clamp(HITBOX_SCALE_STEPS[0], 1.0),
+        });
+        world.insert_resource(ArenaAspectSetting {
+            value: self.arena_aspect,
+        });
+        world.insert_resource(ArenaFraction {
+            value: self.arena_fraction.clamp(ARENA_FRACTION_STEPS[0], 1.0),
+        });
+        world.insert_resource(DangerHighlightEnabled {
+            value: self.danger_highlight,
+        });
+        world.insert_resource(RumbleStrength {
+            value: self.rumble_strength.clamp(0.0, 1.0),
+        });
+        world.insert_resource(CameraFollowStrength {
+            value: self.camera_follow.clamp(0.0, 1.0),
+        });
+        world.insert_resource(ScreenshakeConfig {
+            scale: self.screenshake.scale.max(0.0),
+            ..self.screenshake
+        });
+        world.insert_resource(MovementConfig {
+            speed_scale: self.movement.speed_scale.clamp(
+                PLAYER_SPEED_SCALE_STEPS[0],
+                PLAYER_SPEED_SCALE_STEPS[PLAYER_SPEED_SCALE_STEPS.len() - 1],
+            ),
+            ..self.movement
+        });
+        world.insert_resource(SuddenDeathConfig {
+            threshold: self.sudden_death.threshold.max(0.0),
+            ..self.sudden_death
+        });
+        world.insert_resource(GrowingBulletsEnabled {
+            value: self.growing_bullets,
+        });
+        world.insert_resource(PlayerTrailEnabled {
+            value: self.player_trail,
+        });
+        world.insert_resource(RibbonTrailsEnabled {
+            value: self.ribbon_trails,
+        });
+        world.insert_resource(AimModeSetting {
+            value: self.aim_mode,
+        });
+        world.insert_resource(FrameRateAim {
+            value: self.frame_rate_aim,
+        });
+        world.insert_resource(FireModeSetting {
+            value: self.fire_mode,
+        });
+        world.insert_resource(TapFireEnabled {
+            value: self.tap_fire,
+        });
+        world.insert_resource(self.shot_pattern);
+        world.insert_resource(BulletColorSetting {
+            value: self.bullet_color,
+        });
+        world.insert_resource(KeyBindings {
+            fire_key: self.fire_key.into(),
+            fire_mouse: MouseButton::Left,
+            fire_gamepad: GamepadButton::RightTrigger2,
+            aim_up: KeyCode::KeyI,
+            aim_down: KeyCode::KeyK,
+            aim_left: KeyCode::KeyJ,
+            aim_right: KeyCode::KeyL,
+            bomb_key: KeyCode::KeyE,
+            bomb_mouse: MouseButton::Right,
+            bomb_gamepad: GamepadButton::South,
+            focus_key: KeyCode::ShiftLeft,
+            focus_gamepad: GamepadButton::LeftTrigger,
+            pause_gamepad: self.pause_button.into(),
+            pause_on_guide: self.guide_pauses,
+        });
+        world.insert_resource(PauseOnDisconnect {
+            value: self.pause_on_disconnect,
+        });
+        world.insert_resource(MaxRunSeconds {
+            value: self.max_run_seconds.max(0.0),
+        });
+        world.insert_resource(TimerPrecisionSetting {
+            value: self.timer_precision,
+        });
+        world.insert_resource(TutorialSeen {
+            value: self.seen_tutorial,
+        });
+        world.insert_resource(SessionStatsEnabled {
+            value: self.session_stats,
+        });
+        world.insert_resource(BackgroundTintEnabled {
+            value: self.background_tint,
+        });
+        world.insert_resource(DeathCinematicEnabled {
+            value: self.death_cinematic,
+        });
+        world.insert_resource(ReticleSettings {
+            visible: self.reticle_visible,
+            shape: self.reticle_shape,
+            size: self.reticle_size.clamp(
+                RETICLE_SIZE_STEPS[0],
+                RETICLE_SIZE_STEPS[RETICLE_SIZE_STEPS.len() - 1],
+            ),
+        });
+        world.insert_resource(FontSettings {
+            path: self.font_path,
+            scale: self.font_scale.clamp(
+                FONT_SCALE_STEPS[0],
+                FONT_SCALE_STEPS[FONT_SCALE_STEPS.len() - 1],
+            ),
+        });
+        world.insert_resource(Brightness {
+            value: self.brightness.clamp(
+                BRIGHTNESS_STEPS[0],
+                BRIGHTNESS_STEPS[BRIGHTNESS_STEPS.len() - 1],
+            ),
+        });
+    }
+}
+
+// every user-facing option, bundled so the menus can read and change them through a single parameter
+#[derive(SystemParam)]
+struct GameSettings<'w> {
+    game_mode: ResMut<'w, SelectedGameMode>,
+    particle_quality: ResMut<'w, ParticleQualitySetting>,
+    reduce_motion: ResMut<'w, ReduceMotion>,
+    bloom: ResMut<'w, BloomEnabled>,
+    practice: ResMut<'w, PracticeMode>,
+    assist: ResMut<'w, AssistMode>,
+    revive: ResMut<'w, ReviveEnabled>,
+    present_mode: ResMut<'w, PresentModeSetting>,
+    waves: ResMut<'w, WavesEnabled>,
+    elasticity: ResMut<'w, BounceElasticity>,
+    heatmap: ResMut<'w, HeatmapEnabled>,
+    aim_distance: ResMut<'w, GamepadAimDistance>,
+    aim_assist: ResMut<'w, AimAssist>,
+    threat_indicators: ResMut<'w, ThreatIndicatorsEnabled>,
+    velocity_vectors: ResMut<'w, VelocityVectorsEnabled>,
+    hitbox_scale: ResMut<'w, HitboxScale>,
+    arena_aspect: ResMut<'w, ArenaAspectSetting>,
+    arena_fraction: ResMut<'w, ArenaFraction>,
+    danger_highlight: ResMut<'w, DangerHighlightEnabled>,
+    rumble_strength: ResMut<'w, RumbleStrength>,
+    screenshake: ResMut<'w, ScreenshakeConfig>,
+    camera_follow: ResMut<'w, CameraFollowStrength>,
+    movement: ResMut<'w, MovementConfig>,
+    growing_bullets: ResMut<'w, GrowingBulletsEnabled>,
+    player_trail: ResMut<'w, PlayerTrailEnabled>,
+    ribbon_trails: ResMut<'w, RibbonTrailsEnabled>,
+    aim_mode: ResMut<'w, AimModeSetting>,
+    frame_rate_aim: ResMut<'w, FrameRateAim>,
+    fire_mode: ResMut<'w, FireModeSetting>,
+    tap_fire: ResMut<'w, TapFireEnabled>,
+    key_bindings: ResMut<'w, KeyBindings>,
+    shot_pattern: ResMut<'w, ShotPattern>,
+    bullet_color: ResMut<'w, BulletColorSetting>,
+    max_run_seconds: ResMut<'w, MaxRunSeconds>,
+    sudden_death: ResMut<'w, SuddenDeathConfig>,
+    timer_precision: ResMut<'w, TimerPrecisionSetting>,
+    session_stats: ResMut<'w, SessionStatsEnabled>,
+    seen_tutorial: ResMut<'w, TutorialSeen>,
+    reticle: ResMut<'w, ReticleSettings>,
+    background_tint: ResMut<'w, BackgroundTintEnabled>,
+    death_cinematic: ResMut<'w, DeathCinematicEnabled>,
+    pause_on_disconnect: ResMut<'w, PauseOnDisconnect>,
+    font: ResMut<'w, FontSettings>,
+    brightness: ResMut<'w, Brightness>,
+}
+
+impl GameSettings<'_> {
+    fn label(&self, setting: SettingLabel) -> String {
+        match setting {
+            SettingLabel::GameMode => match self.game_mode.value {
+                GameMode::Endless => "Mode: Endless".to_string(),
+                GameMode::Timed => "Mode: Timed".to_string(),
+                GameMode::Survival => "Mode: Survival".to_string(),
+                GameMode::ScoreAttack => "Mode: Score attack".to_string(),
+            },
+            SettingLabel::ParticleQuality => match self.particle_quality.value {
+                ParticleQuality::Low => "Particles: Low".to_string(),
+                ParticleQuality::Medium => "Particles: Medium".to_string(),
+                ParticleQuality::High => "Particles: High".to_string(),
+            },
+            SettingLabel::ReduceMotion => on_off_label("Reduce motion", self.reduce_motion.value),
+            SettingLabel::Bloom => on_off_label("Bloom", self.bloom.value),
+            SettingLabel::BackgroundTint => {
+                on_off_label("Background tint", self.background_tint.value)
+            }
+            SettingLabel::DeathCinematic => on_off_label("Death zoom", self.death_cinematic.value),
+            SettingLabel::Practice => on_off_label("Practice", self.practice.value),
+            SettingLabel::Assist => on_off_label("Assist mode", self.assist.value),
+            SettingLabel::Revive => on_off_label("Revive", self.revive.value),
+            SettingLabel::PresentMode => match self.present_mode.value {
+                PresentMode::AutoNoVsync => "VSync: Off".to_string(),
+                PresentMode::Mailbox => "VSync: Mailbox".to_string(),
+                _ => "VSync: On".to_string(),
+            },
+            SettingLabel::Waves => on_off_label("Edge waves", self.waves.value),
+            SettingLabel::Heatmap => on_off_label("Heatmap", self.heatmap.value),
+            SettingLabel::ArenaAspect => match self.arena_aspect.value {
+                ArenaAspect::Full => "Arena: Full".to_string(),
+                ArenaAspect::Widescreen => "Arena: 16:9".to_string(),
+                ArenaAspect::Standard => "Arena: 4:3".to_string(),
+            },
+            SettingLabel::ArenaFraction => {
+                format!("Arena size: {:.0}%", self.arena_fraction.value * 100.0)
+            }
+            SettingLabel::RumbleStrength => {
+                if self.rumble_strength.value > 0.0 {
+                    format!("Rumble: {:.0}%", self.rumble_strength.value * 100.0)
+                } else {
+                    "Rumble: Off".to_string()
+                }
+            }
+            SettingLabel::ScreenshakeScale => {
+                if self.screenshake.scale > 0.0 {
+                    format!("Screenshake: {:.0}%", self.screenshake.scale * 100.0)
+                } else {
+                    "Screenshake: Off".to_string()
+                }
+            }
+            SettingLabel::CameraFollow => {
+                if self.camera_follow.value > 0.0 {
+                    format!("Camera follow: {:.0}%", self.camera_follow.value * 100.0)
+                } else {
+                    "Camera follow: Off".to_string()
+                }
+            }
+            SettingLabel::PlayerSpeed => {
+                format!("Player speed: {:.0}%", self.movement.speed_scale * 100.0)
+            }
+            SettingLabel::PlayerTrail => on_off_label("Player trail", self.player_trail.value),
+            SettingLabel::RibbonTrails => {
+                if self.ribbon_trails.value {
+                    "Bullet trails: Ribbon".to_string()
+                } else {
+                    "Bullet trails: Dots".to_string()
+                }
+            }
+            SettingLabel::GrowingBullets => {
+                on_off_label("Growing bullets", self.growing_bullets.value)
+            }
+            SettingLabel::DangerHighlight => {
+                on_off_label("Danger pulse", self.danger_highlight.value)
+            }
+            SettingLabel::HitboxScale => {
+                format!("Hitbox: {:.0}%", self.hitbox_scale.value * 100.0)
+            }
+            SettingLabel::ThreatIndicators => {
+                on_off_label("Threat arrows", self.threat_indicators.value)
+            }
+            SettingLabel::VelocityVectors => {
+                on_off_label("Velocity lines", self.velocity_vectors.value)
+            }
+            SettingLabel::AimDistance => {
+                if self.aim_distance.value >= GAMEPAD_AIM_DISTANCE_FAR {
+                    "Aim distance: Far".to_string()
+                } else {
+                    "Aim distance: Near".to_string()
+                }
+            }
+            SettingLabel::AimMode => match self.aim_mode.value {
+                AimMode::Relative => "Mouse aim: Relative".to_string(),
+                AimMode::Absolute => "Mouse aim: Absolute".to_string(),
+            },
+            SettingLabel::FrameRateAim => {
+                on_off_label("Low latency aim", self.frame_rate_aim.value)
+            }
+            SettingLabel::FireMode => match self.fire_mode.value {
+                FireMode::Auto => "Fire: Auto".to_string(),
+                FireMode::Manual => "Fire: Hold".to_string(),
+            },
+            SettingLabel::TapFire => on_off_label("Tap to fire", self.tap_fire.value),
+            SettingLabel::FireKey => format!(
+                "Fire key: {}",
+                FireKey::from(self.key_bindings.fire_key).label()
+            ),
+            SettingLabel::PauseButton => format!(
+                "Pause button: {}",
+                PauseButton::from(self.key_bindings.pause_gamepad).label()
+            ),
+            SettingLabel::GuidePauses => {
+                on_off_label("Guide button pauses", self.key_bindings.pause_on_guide)
+            }
+            SettingLabel::PauseOnDisconnect => {
+                on_off_label("Pause on disconnect", self.pause_on_disconnect.value)
+            }
+            SettingLabel::BulletColor => match self.bullet_color.value {
+                BulletColorMode::Oscillate => "Bullet color: Cycling".to_string(),
+                BulletColorMode::Static { hue } => format!("Bullet color: Hue {:.0}", hue),
+            },
+            SettingLabel::ShotPattern => match *self.shot_pattern {
+                ShotPattern::Single => "Shot: Single".to_string(),
+                ShotPattern::Spread { count, .. } => format!("Shot: Spread x{}", count),
+            },
+            SettingLabel::TimerPrecision => match self.timer_precision.value {
+                TimerPrecision::Centi => "Timer: 1/100 s".to_string(),
+                TimerPrecision::Milli => "Timer: 1/1000 s".to_string(),
+            },
+            SettingLabel::SessionStats => on_off_label("Session stats", self.session_stats.value),
+            SettingLabel::MaxRunSeconds => {
+                if self.max_run_seconds.value > 0.0 {
+                    format!("Run cap: {:.0} min", self.max_run_seconds.value / 60.0)
+                } else {
+                    "Run cap: Unlimited".to_string()
+                }
+            }
+            SettingLabel::SuddenDeath => {
+                if self.sudden_death.threshold > 0.0 {
+                    format!("Sudden death: {:.0}s", self.sudden_death.threshold)
+                } else {
+                    "Sudden death: Off".to_string()
+                }
+            }
+            SettingLabel::ReticleVisible => on_off_label("Reticle", self.reticle.visible),
+            SettingLabel::ReticleShape => match self.reticle.shape {
+                ReticleShape::Dot => "Reticle shape: Dot".to_string(),
+                ReticleShape::Crosshair => "Reticle shape: Cross".to_string(),
+            },
+            SettingLabel::ReticleSize => {
+                format!("Reticle size: {:.0}%", self.reticle.size * 100.0)
+            }
+            SettingLabel::FontScale => format!("Text size: {:.0}%", self.font.scale * 100.0),
+            SettingLabel::Brightness => {
+                format!("Brightness: {:.0}%", self.brightness.value * 100.0)
+            }
+            SettingLabel::AimAssist => {
+                if self.aim_assist.value > 0.0 {
+                    format!("Aim assist: {:.0}%", self.aim_assist.value * 100.0)
+                } else {
+                    "Aim assist: Off".to_string()
+                }
+            }
+            SettingLabel::Elasticity => {
+                if self.elasticity.value >= BOUNCE_ELASTICITY_HIGH {
+                    "Bounce speedup: High".to_string()
+                } else if self.elasticity.value >= BOUNCE_ELASTICITY_LOW {
+                    "Bounce speedup: Low".to_string()
+                } else {
+                    "Bounce speedup: Off".to_string()
+                }
+            }
+        }
+    }
+
+    fn to_config(&self) -> GameConfig {
+        GameConfig {
+            version: CONFIG_VERSION,
+            game_mode: self.game_mode.value,
+            particle_quality: self.particle_quality.value,
+            reduce_motion: self.reduce_motion.value,
+            bloom: self.bloom.value,
+            practice: self.practice.value,
+            assist: self.assist.value,
+            revive: self.revive.value,
+            vsync: self.present_mode.value.into(),
+            waves: self.waves.value,
+            elasticity: self.elasticity.value,
+            heatmap: self.heatmap.value,
+            aim_distance: self.aim_distance.value,
+            aim_assist: self.aim_assist.value,
+            threat_indicators: self.threat_indicators.value,
+            velocity_vectors: self.velocity_vectors.value,
+            hitbox_scale: self.hitbox_scale.value,
+            arena_aspect: self.arena_aspect.value,
+            arena_fraction: self.arena_fraction.value,
+            danger_highlight: self.danger_highlight.value,
+            rumble_strength: self.rumble_strength.value,
+            camera_follow: self.camera_follow.value,
+            screenshake: *self.screenshake,
+            movement: *self.movement,
+            sudden_death: *self.sudden_death,
+            player_trail: self.player_trail.value,
+            ribbon_trails: self.ribbon_trails.value,
+            growing_bullets: self.growing_bullets.value,
+            aim_mode: self.aim_mode.value,
+            frame_rate_aim: self.frame_rate_aim.value,
+            fire_mode: self.fire_mode.value,
+            tap_fire: self.tap_fire.value,
+            fire_key: self.key_bindings.fire_key.into(),
+            shot_pattern: *self.shot_pattern,
+            bullet_color: self.bullet_color.value,
+            pause_button: self.key_bindings.pause_gamepad.into(),
+            guide_pauses: self.key_bindings.pause_on_guide,
+            pause_on_disconnect: self.pause_on_disconnect.value,
+            max_run_seconds: self.max_run_seconds.value,
+            timer_precision: self.timer_precision.value,
+            session_stats: self.session_stats.value,
+            seen_tutorial: self.seen_tutorial.value,
+            reticle_visible: self.reticle.visible,
+            reticle_shape: self.reticle.shape,
+            reticle_size: self.reticle.size,
+            background_tint: self.background_tint.value,
+            death_cinematic: self.death_cinematic.value,
+            font_path: self.font.path.clone(),
+            font_scale: self.font.scale,
+            brightness: self.brightness.value,
+        }
+    }
+
+    fn any_changed(&self) -> bool {
+        self.game_mode.is_changed()
+            || self.particle_quality.is_changed()
+            || self.reduce_motion.is_changed()
+            || self.bloom.is_changed()
+            || self.practice.is_changed()
+            || self.assist.is_changed()
+            || self.revive.is_changed()
+            || self.present_mode.is_changed()
+            || self.waves.is_changed()
+            || self.elasticity.is_changed()
+            || self.heatmap.is_changed()
+            || self.aim_distance.is_changed()
+            || self.aim_assist.is_changed()
+            || self.threat_indicators.is_changed()
+            || self.velocity_vectors.is_changed()
+            || self.hitbox_scale.is_changed()
+            || self.arena_aspect.is_changed()
+            || self.arena_fraction.is_changed()
+            || self.danger_highlight.is_changed()
+            || self.rumble_strength.is_changed()
+            || self.camera_follow.is_changed()
+            || self.screenshake.is_changed()
+            || self.movement.is_changed()
+            || self.player_trail.is_changed()
+            || self.ribbon_trails.is_changed()
+            || self.growing_bullets.is_changed()
+            || self.aim_mode.is_changed()
+            || self.frame_rate_aim.is_changed()
+            || self.fire_mode.is_changed()
+            || self.tap_fire.is_changed()
+            || self.key_bindings.is_changed()
+            || self.shot_pattern.is_changed()
+            || self.bullet_color.is_changed()
+            || self.max_run_seconds.is_changed()
+            || self.sudden_death.is_changed()
+            || self.timer_precision.is_changed()
+            || self.session_stats.is_changed()
+            || self.seen_tutorial.is_changed()
+            || self.reticle.is_changed()
+            || self.background_tint.is_changed()
+            || self.death_cinematic.is_changed()
+            || self.pause_on_disconnect.is_changed()
+            || self.font.is_changed()
+            || self.brightness.is_changed()
+    }
+}
+
+// every text size is a fraction of the window height, scaled up for players who need bigger text
+fn font_size(h: u32, divisor: u32, font: &FontSettings) -> f32 {
+    (h / divisor) as f32 * font.scale
+}
+
+fn on_off_label(name: &str, value: bool) -> String {
+    if value {
+        format!("{}: On", name)
+    } else {
+        format!("{}: Off", name)
+    }
+}
+
+#[derive(Resource)]
+struct ScreenshakeIntensity {
+    value: f32,
+}
+
+// player movement tunables, the settings menu only touches the scale
+#[derive(Resource, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct MovementConfig {
+    speed: f32,
+    speed_scale: f32,
+    intensity_bonus: f32, // the player is this much faster at full intensity, off unless set in the config file
+    keyboard_aim_speed: f32,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        MovementConfig {
+            speed: PLAYER_MOVEMENT_SPEED_NORMALIZED,
+            speed_scale: 1.0,
+            intensity_bonus: 0.0,
+            keyboard_aim_speed: KEYBOARD_AIM_SPEED,
+        }
+    }
+}
+
+impl MovementConfig {
+    fn speed_at(&self, intensity: &Intensity) -> f32 {
+        self.speed * self.speed_scale * (1.0 + intensity.value * self.intensity_bonus)
+    }
+}
+
+// escalation past a survival time that makes runs end on their own, the settings menu only touches the threshold
+#[derive(Resource, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct SuddenDeathConfig {
+    threshold: f32,
+    ramp_duration: f32,
+    speed_bonus: f32,
+    fire_rate_bonus: f32,
+}
+
+impl Default for SuddenDeathConfig {
+    fn default() -> Self {
+        SuddenDeathConfig {
+            threshold: 0.0,
+            ramp_duration: SUDDEN_DEATH_RAMP_DURATION,
+            speed_bonus: SUDDEN_DEATH_SPEED_BONUS,
+            fire_rate_bonus: SUDDEN_DEATH_FIRE_RATE_BONUS,
+        }
+    }
+}
+
+impl SuddenDeathConfig {
+    fn sample(&self, survival_time: f32) -> f32 {
+        if self.threshold <= 0.0 {
+            return 0.0;
+        }
+        ((survival_time - self.threshold) / self.ramp_duration.max(f32::EPSILON)).clamp(0.0, 1.0)
+    }
+}
+
+// every screenshake tunable, the config file can override each one and the settings menu only touches the scale
+#[derive(Resource, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct ScreenshakeConfig {
+    on_shoot: f32,
+    on_bounce: f32,
+    on_death: f32,
+    on_bomb: f32,
+    velocity: f32,
+    dampening: f32,
+    scale: f32,
+}
+
+impl Default for ScreenshakeConfig {
+    fn default() -> Self {
+        ScreenshakeConfig {
+            on_shoot: SCREENSHAKE_ON_SHOOT,
+            on_bounce: SCREENSHAKE_ON_BOUNCE,
+            on_death: SCREENSHAKE_ON_DEATH,
+            on_bomb: SCREENSHAKE_ON_BOMB,
+            velocity: SCREENSHAKE_VELOCITY,
+            dampening: SCREENSHAKE_DAMPENING,
+            scale: 1.0,
+        }
+    }
+}
+
+impl ScreenshakeConfig {
+    fn kick(&self, amount: f32) -> f32 {
+        amount * self.scale
+    }
+}
+
+#[derive(Resource)]
+struct ZoomPunch {
+    value: f32,
+}
+
+#[derive(Resource)]
+struct CameraFollowStrength {
+    value: f32,
+}
+
+// the resting camera framing that screenshake and the zoom punch are layered on
+#[derive(Resource)]
+struct CameraFollow {
+    offset: Vec2,
+    zoom: f32,
+}
+
+// the run is already over while this plays, the camera keeps the framing until the next reset
+#[derive(Resource, Default)]
+struct DeathCinematic {
+    active: bool,
+    elapsed: f32,
+    focus: Vec2,
+}
+
+#[derive(Resource)]
+struct ScreenFlash {
+    color: Color,
+    intensity: f32,
+}
+
+#[derive(Resource)]
+struct BulletRenderComponents {
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+    heatmap_material: Handle<ColorMaterial>,
+}
+
+#[derive(Resource)]
+struct MenuRepeatTimer {
+    direction: f32,
+    timer: Timer,
+}
+
+#[derive(Resource)]
+struct PauseInputBuffer {
+    pending: bool,
+    cooldown: f32,
+}
+
+// the gamepad controlling the player, the first one to give any input claims it
+#[derive(Resource)]
+struct ActiveGamepad {
+    value: Option<Entity>,
+}
+
+// what the player asks for during the current fixed step, the only input the movement systems look at
+#[derive(Resource, Clone, Copy, Default)]
+struct InputState {
+    movement: Vec2,
+    // mouse movement since the last step, with y pointing up like the arena
+    aim_motion: Vec2,
+    // direction held on the keyboard aim keys
+    aim_keys: Vec2,
+    // raw right stick of the active gamepad, none while no gamepad is connected
+    aim_stick: Option<Vec2>,
+    // cursor position in arena coordinates, none while it's outside the window
+    cursor: Option<Vec2>,
+    // slows the player down and shows the hitbox
+    focus: bool,
+}
+
+// one InputState per fixed step, replacing whatever the devices reported for as long as it exists
+// lets the tests play deterministic runs, next doubles as the count of fixed steps played so far
+#[cfg(test)]
+#[derive(Resource)]
+struct ScriptedInput {
+    steps: Vec<InputState>,
+    next: usize,
+}
+
+#[derive(Resource)]
+struct PrimaryControlDevice {
+    value: ControlDevice,
+}
+
+#[derive(Resource)]
+// w and h are the window size, the half sizes and shorter dimension describe the arena within it
+struct DisplayProperties {
+    w: f32,
+    h: f32,
+    half_w: f32,
+    half_h: f32,
+    shorter_dimension: f32,
+}
+
+impl DisplayProperties {
+    fn set_size(&mut self, w: f32, h: f32, aspect: Option<f32>, fraction: f32) {
+        let (arena_w, arena_h) = match aspect {
+            Some(ratio) if w / h > ratio => (h * ratio, h),
+            Some(ratio) => (w, w / ratio),
+            None => (w, h),
+        };
+        let (arena_w, arena_h) = (
+            (arena_w * fraction).max(MIN_ARENA_SIZE),
+            (arena_h * fraction).max(MIN_ARENA_SIZE),
+        );
+        self.w = w;
+        self.h = h;
+        self.half_w = arena_w / 2.;
+        self.half_h = arena_h / 2.;
+        self.shorter_dimension = if arena_w < arena_h { arena_w } else { arena_h };
+    }
+}
+
+#[derive(Component)]
+enum MenuButtonAction {
+    Play,
+    Quit,
+    Resume,
+    Restart,
+    ToMenu,
+    ToSettings,
+    CycleGameMode,
+    CycleParticleQuality,
+    ToggleReduceMotion,
+    ToggleBloom,
+    TogglePractice,
+    ToggleAssist,
+    ToggleRevive,
+    CyclePresentMode,
+    ToggleWaves,
+    CycleElasticity,
+    ToggleHeatmap,
+    CycleAimDistance,
+    CycleAimAssist,
+    ToggleThreatIndicators,
+    ToggleVelocityVectors,
+    CycleSeed,
+    CycleHitboxScale,
+    CycleArenaAspect,
+    CycleArenaFraction,
+    ToggleDangerHighlight,
+    CycleRumbleStrength,
+    CycleScreenshakeScale,
+    CycleCameraFollow,
+    CyclePlayerSpeed,
+    TogglePlayerTrail,
+    ToggleRibbonTrails,
+    ToggleGrowingBullets,
+    CycleAimMode,
+    ToggleFrameRateAim,
+    CycleFireMode,
+    ToggleTapFire,
+    CycleFireKey,
+    CycleShotPattern,
+    CycleBulletColor,
+    CyclePauseButton,
+    ToggleGuidePauses,
+    TogglePauseOnDisconnect,
+    CycleMaxRunSeconds,
+    CycleSuddenDeath,
+    CycleTimerPrecision,
+    ToggleSessionStats,
+    ToggleReticle,
+    CycleReticleShape,
+    CycleReticleSize,
+    ToggleBackgroundTint,
+    ToggleDeathCinematic,
+    CycleFontScale,
+    CycleBrightness,
+    ToData,
+    ResetBestRun,
+    ResetSettings,
+}
+
+#[derive(Component, Clone, Copy, Eq, PartialEq)]
+enum DataAction {
+    ResetBestRun,
+    ResetSettings,
+}
+
+impl DataAction {
+    fn label(self, armed: bool) -> &'static str {
+        match (self, armed) {
+            (DataAction::ResetBestRun, false) => "Reset best run",
+            (DataAction::ResetSettings, false) => "Reset all settings",
+            (_, true) => "Press again to confirm",
+        }
+    }
+}
+
+// the line on the data page describing the stored best run
+#[derive(Component)]
+struct BestRunSummary;
+
+#[derive(Component)]
+struct SelectedOption;
+
+// a button that stays visible but can't be selected or pressed
+#[derive(Component)]
+struct Disabled;
+
+#[derive(Component)]
+struct Player {
+    bullet_timer: f32,
+    since_shot: Option<f32>, // none until the first shot, so a tap can't skip the opening delay
+}
+#[derive(Component)]
+struct TrailParticleSpawner {
+    timer: Timer,
+}
+
+// the positions a bullet sampled at the trail interval, newest first, only filled while ribbon trails are on
+#[derive(Component, Default)]
+struct RibbonTrail {
+    points: VecDeque<Vec2>,
+}
+
+#[derive(Component)]
+struct RibbonTrailMesh;
+
+#[derive(Component)]
+struct PlayerAim;
+
+#[derive(Component)]
+struct GhostPlayer {
+    index: usize,
+}
+
+// telegraphs an incoming edge bullet before it actually appears
+#[derive(Component)]
+struct WaveWarning {
+    timer: Timer,
+    velocity: Vec3,
+}
+
+#[derive(Component)]
+struct Bullet;
+
+// the player's real hit circle, only shown while focusing
+#[derive(Component)]
+struct HitboxIndicator;
+
+// only visual, collisions use the full radius from the first frame
+// the shared bullet material can't carry a per-bullet alpha, so a fading bullet gets its own until it's fully in
+#[derive(Component, Default)]
+struct BulletFadeIn {
+    age: f32,
+    material: Option<Handle<ColorMaterial>>,
+}
+
+// added to the player on the fixed step it gets hit
+#[derive(Component)]
+struct Dead;
+
+#[derive(Message)]
+struct PlayerDied;
+
+struct TrailParticle {
+    position: Vec2,
+    lifetime: f32,
+    max_lifetime: f32,
+    base_color: Color,
+}
+
+struct BounceParticle {
+    position: Vec2,
+    lifetime: f32,
+    velocity: Vec3,
+}
+
+// an arc of dots on the arena edge where a bullet bounced, widening along the wall as it fades
+struct WallRipple {
+    position: Vec2,
+    normal: Vec2,
+    lifetime: f32,
+    dots: usize,
+}
+
+// every live particle, drawn through one shared mesh per kind instead of an entity each
+#[derive(Resource, Default)]
+struct ParticleBatch {
+    trail: Vec<TrailParticle>,
+    bounce: Vec<BounceParticle>,
+    ripple: Vec<WallRipple>,
+}
+
+#[derive(Component)]
+struct TrailParticleMesh;
+
+#[derive(Component)]
+struct BounceParticleMesh;
+
+#[derive(Component)]
+struct WallRippleMesh;
+
+#[derive(Component)]
+struct ScreenEdgeBouncer {
+    velocity: Vec3,
+}
+
+#[derive(Component)]
+struct ThreatIndicator;
+
+#[derive(Component)]
+struct HeatmapStamp;
+
+// a streak at a fixed angle around the screen, the phase keeps neighbouring lines from sliding in sync
+#[derive(Component)]
+struct SpeedLine {
+    angle: f32,
+    phase: f32,
+}
+
+// every line fades together, so they share one material
+#[derive(Resource)]
+struct SpeedLineMaterial {
+    handle: Handle<ColorMaterial>,
+}
+
+#[derive(Component)]
+struct SeedLabel;
+
+#[derive(Component)]
+struct LoadingText;
+
+// purely decorative, never collides with anything
+#[derive(Component)]
+struct MenuBackgroundBall;
+
+#[derive(Component)]
+struct ButtonsHolder;
+
+#[derive(Component)]
+struct ScoreDisplay;
+
+#[derive(Component)]
+struct BombDisplay;
+
+#[derive(Component)]
+struct SuddenDeathWarning;
+
+// time left until the run cap ends the run, only spawned while a cap is set
+#[derive(Component)]
+struct RunCapDisplay;
+
+// fills toward the win condition in timed mode, or toward the best run in endless
+#[derive(Component)]
+struct ProgressBar;
+
+#[derive(Component)]
+struct ScreenFlashOverlay;
+
+// covers everything outside of the arena with its borders
+#[derive(Component)]
+struct ArenaLetterbox;
+
+#[derive(Component)]
+struct ControlDeviceDisplay;
+
+#[derive(Component, Clone, Copy)]
+enum SettingLabel {
+    GameMode,
+    ParticleQuality,
+    ReduceMotion,
+    Bloom,
+    Practice,
+    Assist,
+    Revive,
+    PresentMode,
+    Waves,
+    Elasticity,
+    Heatmap,
+    AimDistance,
+    AimAssist,
+    ThreatIndicators,
+    VelocityVectors,
+    HitboxScale,
+    ArenaAspect,
+    ArenaFraction,
+    DangerHighlight,
+    RumbleStrength,
+    ScreenshakeScale,
+    CameraFollow,
+    PlayerSpeed,
+    PlayerTrail,
+    RibbonTrails,
+    GrowingBullets,
+    AimMode,
+    FrameRateAim,
+    FireMode,
+    TapFire,
+    FireKey,
+    ShotPattern,
+    BulletColor,
+    PauseButton,
+    GuidePauses,
+    PauseOnDisconnect,
+    MaxRunSeconds,
+    SuddenDeath,
+    TimerPrecision,
+    SessionStats,
+    ReticleVisible,
+    ReticleShape,
+    ReticleSize,
+    BackgroundTint,
+    DeathCinematic,
+    FontScale,
+    Brightness,
+}
+
+/// The whole game: its resources, states and systems.
+///
+/// Expects `DefaultPlugins` to already be added, the window setup is left to the embedding app.
+pub struct DodgeBallPlugin;
+
+impl Plugin for DodgeBallPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(Color::linear_rgb(0.00, 0.00, 0.00)));
+        app.insert_resource(DisplayProperties {
+            w: 640.,
+            h: 480.,
+            half_w: 320.,
+            half_h: 240.,
+            shorter_dimension: 480.,
+        });
+        app.insert_resource(PrimaryControlDevice {
+            value: ControlDevice::Keyboard,
+        });
+        app.insert_resource(ActiveGamepad { value: None });
+        app.insert_resource(Score { value: 0.0 });
+        app.insert_resource(ScoreAttack { points: 0.0 });
+        app.init_resource::<PendingSaves>();
+        app.init_resource::<ParticleBatch>();
+        app.init_resource::<InputState>();
+        app.init_resource::<SessionStats>();
+        app.init_resource::<Recovery>();
+        app.insert_resource(Bombs {
+            charges: BOMB_CHARGES,
+            freeze_remaining: 0.0,
+        });
+        app.insert_resource(QuickRestart { value: false });
+        app.init_resource::<DataConfirm>();
+        app.insert_resource(Intensity {
+            survival_time: 0.0,
+            value: 0.0,
+            sudden_death: 0.0,
+        });
+        app.insert_resource(BulletGrowth { value: 1.0 });
+        app.insert_resource(IntensityCurve {
+            ramp_duration: INTENSITY_RAMP_DURATION,
+            exponent: INTENSITY_RAMP_EXPONENT,
+        });
+        load_config().insert_resources(app.world_mut());
+        app.insert_resource(GhostRecorder {
+            samples: Vec::new(),
+        });
+        app.insert_resource(load_best_ghost());
+        app.insert_resource(HeatmapStamper {
+            timer: Timer::from_seconds(HEATMAP_STAMP_INTERVAL, TimerMode::Repeating),
+            stamps: VecDeque::new(),
+        });
+        app.insert_resource(WaveSpawner {
+            timer: Timer::from_seconds(WAVE_INTERVAL_START, TimerMode::Once),
+        });
+        let seeded_rng = ChaCha8Rng::seed_from_u64(2137);
+        app.insert_resource(RandomSource(seeded_rng));
+        app.insert_resource(CurrentSeed { value: 2137 });
+        app.insert_resource(SeedEntry { value: None });
+        app.insert_resource(ScreenshakeIntensity { value: 0.0 });
+        app.insert_resource(PauseInputBuffer {
+            pending: false,
+            cooldown: 0.0,
+        });
+        app.insert_resource(MenuRepeatTimer {
+            direction: 0.0,
+            timer: Timer::from_seconds(MENU_REPEAT_INITIAL_DELAY, TimerMode::Once),
+        });
+        app.insert_resource(ZoomPunch { value: 0.0 });
+        app.insert_resource(CameraFollow {
+            offset: Vec2::ZERO,
+            zoom: 1.0,
+        });
+        app.init_resource::<DeathCinematic>();
+        app.init_resource::<ControllerDisconnected>();
+        app.insert_resource(ScreenFlash {
+            color: Color::WHITE,
+            intensity: 0.0,
+        });
+
+        app.add_message::<PlayerDied>();
+
+        app.add_systems(Startup, (init_bullet_data, spawn_particle_meshes).chain());
+        app.add_systems(
+            OnEnter(AppState::Menu),
+            (
+                (main_menu_setup, spawn_menu_background)
+                    .run_if(not(quick_restart_requested))
+                    .before(continue_quick_restart),
+                continue_quick_restart,
+                despawn_player,
+                despawn_player_aim,
+                despawn_bullets,
+                clear_particles,
+                reset_heatmap_stamper,
+                reset_score,
+                reset_intensity,
+                reset_camera_feedback,
+                reset_camera_follow,
+                restore_virtual_time,
+            ),
+        );
+        app.add_systems(
+            OnEnter(AppState::GameOver),
+            (
+                game_over_screen_setup.after(record_session_run),
+                save_ghost_if_best,
+                record_session_run,
+            ),
+        );
+        app.add_systems(
+            OnEnter(AppState::Victory),
+            (victory_screen_setup, record_session_run),
+        );
+        app.add_systems(OnEnter(AppState::Loading), start_loading);
+        app.add_systems(OnEnter(AppState::Paused), pause_menu_setup);
+        app.add_systems(OnExit(AppState::Paused), clear_controller_disconnected);
+        app.add_systems(OnEnter(AppState::Settings), settings_menu_setup);
+        app.add_systems(OnExit(AppState::Settings), flush_pending_saves);
+        app.add_systems(OnEnter(AppState::Data), data_menu_setup);
+        app.add_systems(OnExit(AppState::Menu), flush_pending_saves);
+        app.add_systems(
+            OnTransition {
+                exited: AppState::Menu,
+                entered: AppState::InGame,
+            },
+            (
+                start_run_seed,
+                reset_wave_spawner,
+                reset_bombs,
+                reset_recovery,
+                spawn_player,
+                spawn_player_aim,
+                gameplay_ui_setup,
+                init_bullet_data,
+                spawn_ghost_player,
+                reset_ghost_recorder,
+            ),
+        );
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            (
+                apply_in_game_cursor,
+                spawn_threat_indicators,
+                spawn_speed_lines,
+                reset_camera_feedback,
+            ),
+        );
+        app.add_systems(OnExit(AppState::InGame), make_mouse_visible);
+        app.add_systems(
+            PreUpdate,
+            (
+                check_for_mouse_input,
+                select_active_gamepad,
+                restore_cursor_on_focus_change,
+                flush_pending_saves_on_close_request,
+            ),
+        );
+        app.add_systems(
+            Update,
+            (
+                (
+                    button_react_to_mouse_system,
+                    button_react_to_keyboard_or_gamepad_system,
+                    ensure_menu_selection,
+                    menu_action,
+                )
+                    .run_if(
+                        in_state(AppState::Menu)
+                            .or(in_state(AppState::Paused))
+                            .or(in_state(AppState::Settings))
+                            .or(in_state(AppState::Data)),
+                    )
+                    .run_if(not(any_with_component::<TutorialOverlay>)),
+                (
+                    update_data_labels.run_if(in_state(AppState::Data)),
+                    update_disconnect_notice.run_if(in_state(AppState::Paused)),
+                ),
+                (update_setting_labels, mark_config_dirty)
+                    .run_if(in_state(AppState::Menu).or(in_state(AppState::Settings))),
+                (
+                    update_control_device_display,
+                    edit_seed_entry.run_if(not(any_with_component::<TutorialOverlay>)),
+                    dismiss_tutorial,
+                )
+                    .run_if(in_state(AppState::Menu)),
+                check_loading.run_if(in_state(AppState::Loading)),
+                resize_screen_bounds,
+                handle_game_pausing,
+                spawn_bullet
+                    .after(init_bullet_data)
+                    .run_if(in_state(AppState::InGame).and(player_can_shoot)),
+                (
+                    (handle_score, update_progress_bar).chain(),
+                    (update_intensity, update_bullet_growth).chain(),
+                    stamp_heatmap,
+                    update_threat_indicators,
+                    animate_speed_lines,
+                    show_sudden_death_warning.after(update_intensity),
+                    move_player_aim_per_frame,
+                    (
+                        scale_bullets,
+                        fade_in_bullets
+                            .after(spawn_bullet)
+                            .after(handle_wave_warnings)
+                            .after(oscilate_bullet_colors),
+                    )
+                        .chain(),
+                    animate_idle_player,
+                    show_hitbox_on_focus,
+                    flash_recovering_player,
+                    (use_bomb, update_bomb_display).chain(),
+                )
+                    .run_if(in_state(AppState::InGame)),
+                (spawn_wave_warnings, handle_wave_warnings)
+                    .after(init_bullet_data)
+                    .run_if(in_state(AppState::InGame)),
+                // the menu background balls share the bullet material, so only the settings screen has nothing to color
+                oscilate_bullet_colors.run_if(not(in_state(AppState::Settings))),
+                handle_game_over_continue
+                    .run_if(in_state(AppState::GameOver).or(in_state(AppState::Victory))),
+                // particles spawned this frame only start aging on the next one
+                (
+                    update_trail_settings,
+                    (
+                        handle_trail_particles,
+                        handle_bounce_particles,
+                        handle_wall_ripples,
+                        spawn_bullet_trail,
+                        spawn_player_trail,
+                    )
+                        .chain()
+                        .run_if(in_state(AppState::InGame)),
+                    // also runs once after the menu clears the batch, so no stale particles stay on screen
+                    draw_particle_batches.run_if(resource_changed::<ParticleBatch>),
+                    // hides itself once the bullets are gone, so it needs no state of its own
+                    draw_ribbon_trails,
+                )
+                    .chain(),
+                (
+                    end_run_on_death,
+                    advance_death_cinematic.run_if(in_state(AppState::InGame)),
+                    play_death_sound,
+                    rumble_on_death,
+                    death_screen_feedback,
+                ),
+                // the end screens keep showing the death kick settle, the menu resets the camera on entry
+                (
+                    follow_action.run_if(in_state(AppState::InGame)),
+                    handle_screenshake.run_if(
+                        in_state(AppState::InGame)
+                            .or(in_state(AppState::GameOver))
+                            .or(in_state(AppState::Victory)),
+                    ),
+                )
+                    .chain(),
+                (handle_screen_flash, tint_background, apply_text_brightness),
+                draw_velocity_vectors,
+                apply_bloom_setting,
+                apply_present_mode_setting,
+            ),
+        );
+        app.add_systems(
+            PostUpdate,
+            (
+                (app_init, init_bullet_data).chain().run_if(run_once),
+                button_handle_display,
+            ),
+        );
+        app.add_systems(Last, flush_pending_saves_on_exit);
+        // movement and collisions run on the fixed step, anything else that moves or ages gameplay objects reads
+        // Time<Virtual>, so pausing the virtual clock freezes the whole run. Time<Real> is only for menus and
+        // feedback that should keep going while paused
+        app.add_systems(
+            FixedUpdate,
+            (
+                read_device_input
+                    .before(move_player)
+                    .before(move_player_aim),
+                move_player,
+                clamp_player.after(move_player),
+                move_player_aim.after(clamp_player),
+                clamp_player_aim.after(move_player_aim),
+                move_bouncers,
+                detect_player_death.after(move_bouncers).after(clamp_player),
+                handle_bullet_collision,
+                (record_ghost.after(clamp_player), move_ghost_player)
+                    .run_if(in_state(AppState::InGame)),
+            ),
+        );
+
+        #[cfg(test)]
+        app.add_systems(
+            FixedUpdate,
+            play_scripted_input
+                .run_if(resource_exists::<ScriptedInput>)
+                .after(read_device_input)
+                .before(move_player)
+                .before(move_player_aim),
+        );
+
+        #[cfg(debug_assertions)]
+        {
+            app.add_systems(OnEnter(AppState::Paused), snapshot_paused_run);
+            app.add_systems(
+                Update,
+                (
+                    assert_nothing_advances_while_paused.run_if(in_state(AppState::Paused)),
+                    assert_gameplay_cleaned_up.run_if(in_state(AppState::Menu)),
+                ),
+            );
+        }
+
+        app.init_state::<AppState>();
+    }
+}
+
+// everything in the run that only the paused clocks may advance, debug builds only
+#[cfg(debug_assertions)]
+#[derive(Resource)]
+struct PausedSnapshot {
+    translations: Vec<(Entity, Vec3)>,
+    velocities: Vec<(Entity, Vec3)>,
+    bullet_timer: Option<f32>,
+    score: f32,
+    survival_time: f32,
+}
+
+#[cfg(debug_assertions)]
+type PausedMover = Or<(With<Player>, With<Bullet>, With<ScreenEdgeBouncer>)>;
+
+#[cfg(debug_assertions)]
+impl PausedSnapshot {
+    fn take(
+        movers: &Query<(Entity, &Transform), PausedMover>,
+        bouncers: &Query<(Entity, &ScreenEdgeBouncer)>,
+        player: &Query<&Player>,
+        score: &Score,
+        intensity: &Intensity,
+    ) -> Self {
+        PausedSnapshot {
+            translations: movers
+                .iter()
+                .map(|(entity, transform)| (entity, transform.translation))
+                .collect(),
+            velocities: bouncers
+                .iter()
+                .map(|(entity, bouncer)| (entity, bouncer.velocity))
+                .collect(),
+            bullet_timer: player.iter().next().map(|player| player.bullet_timer),
+            score: score.value,
+            survival_time: intensity.survival_time,
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+fn snapshot_paused_run(
+    mut commands: Commands,
+    movers: Query<(Entity, &Transform), PausedMover>,
+    bouncers: Query<(Entity, &ScreenEdgeBouncer)>,
+    player: Query<&Player>,
+    score: Res<Score>,
+    intensity: Res<Intensity>,
+) {
+    commands.insert_resource(PausedSnapshot::take(
+        &movers, &bouncers, &player, &score, &intensity,
+    ));
+}
+
+// compares values instead of change ticks, a system multiplying by a zero delta still marks them changed
+// catches systems that advance the run on Time<Real> instead of the paused clocks
+#[cfg(debug_assertions)]
+fn assert_nothing_advances_while_paused(
+    mut paused: ResMut<PausedSnapshot>,
+    movers: Query<(Entity, &Transform), PausedMover>,
+    bouncers: Query<(Entity, &ScreenEdgeBouncer)>,
+    player: Query<&Player>,
+    score: Res<Score>,
+    intensity: Res<Intensity>,
+    display_properties: Res<DisplayProperties>,
+) {
+    // a resize while paused legitimately rescales and clamps everything, so the new layout becomes the baseline
+    if display_properties.is_changed() {
+        *paused = PausedSnapshot::take(&movers, &bouncers, &player, &score, &intensity);
+        return;
+    }
+
+    for (entity, before) in &paused.translations {
+        let Ok((_, now)) = movers.get(*entity) else {
+            continue;
+        };
+        assert!(
+            now.translation == *before,
+            "{} moved while paused, some system is likely using the wrong clock",
+            entity
+        );
+    }
+    for (entity, before) in &paused.velocities {
+        let Ok((_, now)) = bouncers.get(*entity) else {
+            continue;
+        };
+        assert!(
+            now.velocity == *before,
+            "{} changed course while paused, some system is likely using the wrong clock",
+            entity
+        );
+    }
+    assert!(
+        player.iter().next().map(|player| player.bullet_timer) == paused.bullet_timer
+            && score.value == paused.score
+            && intensity.survival_time == paused.survival_time,
+        "the run kept going while paused, some system is likely using the wrong clock"
+    );
+}
+
+// every kind of entity a run spawns into the arena
+#[cfg(any(debug_assertions, test))]
+type GameplayLeftover = Or<(
+    With<Player>,
+    With<PlayerAim>,
+    With<Bullet>,
+    With<GhostPlayer>,
+    With<WaveWarning>,
+    With<ThreatIndicator>,
+    With<SpeedLine>,
+    With<HeatmapStamp>,
+)>;
+
+// the menu is where every run ends up, by then nothing spawned for it may be left behind
+// a quick restart leaves the menu before Update runs, so this only sees the settled menu
+#[cfg(debug_assertions)]
+fn assert_gameplay_cleaned_up(
+    leftovers: Query<Entity, GameplayLeftover>,
+    batch: Res<ParticleBatch>,
+) {
+    let count = leftovers.iter().count();
+    assert!(
+        count == 0,
+        "{} gameplay entities outlived the run, something spawned during it lacks a cleanup",
+        count
+    );
+    assert!(
+        batch.trail.is_empty() && batch.bounce.is_empty() && batch.ripple.is_empty(),
+        "particles outlived the run"
+    );
+}
+
+fn app_init(
+    mut commands: Commands,
+    mut game_state: ResMut<NextState<AppState>>,
+    mut window: Single<&mut Window>,
+    bloom: Res<BloomEnabled>,
+    mut display_properties: ResMut<DisplayProperties>,
+    (arena_aspect, arena_fraction): (Res<ArenaAspectSetting>, Res<ArenaFraction>),
+) {
+    let mut camera = commands.spawn((Camera2d::default(), Msaa::Off));
+    if bloom.value {
+        camera.insert((Hdr, bloom_settings()));
+    }
+    // sits above the gameplay, but below every menu (which use the default z index)
+    commands.spawn((
+        ScreenFlashOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            ..default()
+        },
+        BackgroundColor(Color::NONE),
+        GlobalZIndex(-1),
+        Pickable::IGNORE,
+    ));
+    window.resolution.set_scale_factor_override(Some(1.0));
+    // a borderless fullscreen window may never send a resize message, so don't rely on one for the real size
+    display_properties.set_size(
+        window.resolution.physical_width() as f32,
+        window.resolution.physical_height() as f32,
+        arena_aspect.value.ratio(),
+        arena_fraction.value,
+    );
+    commands.spawn((
+        ArenaLetterbox,
+        letterbox_node(&display_properties),
+        BorderColor::all(LETTERBOX_COLOR),
+        GlobalZIndex(-2),
+        Pickable::IGNORE,
+    ));
+    game_state.set(AppState::Loading);
+}
+
+fn bloom_settings() -> Bloom {
+    Bloom {
+        intensity: BLOOM_INTENSITY,
+        ..Bloom::NATURAL
+    }
+}
+
+fn apply_bloom_setting(
+    mut commands: Commands,
+    bloom: Res<BloomEnabled>,
+    camera: Single<Entity, With<Camera2d>>,
+) {
+    if !bloom.is_changed() {
+        return;
+    }
+
+    if bloom.value {
+        commands.entity(*camera).insert((Hdr, bloom_settings()));
+    } else {
+        commands.entity(*camera).remove::<(Hdr, Bloom)>();
+    }
+}
+
+fn apply_present_mode_setting(
+    present_mode: Res<PresentModeSetting>,
+    mut window: Single<&mut Window>,
+) {
+    if !present_mode.is_changed() {
+        return;
+    }
+
+    window.present_mode = present_mode.value;
+}
+
+// bullets are the only thing meant to glow, so with bloom on their color is pushed into HDR range
+fn bullet_color(hue: f32, glow: bool) -> Color {
+    let color = Color::hsv(hue, 1., 0.75);
+    if glow {
+        let linear = color.to_linear();
+        Color::linear_rgb(
+            linear.red * BULLET_GLOW_INTENSITY,
+            linear.green * BULLET_GLOW_INTENSITY,
+            linear.blue * BULLET_GLOW_INTENSITY,
+        )
+    } else {
+        color
+    }
+}
+
+fn init_bullet_data(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    display_properties: Res<DisplayProperties>,
+    bloom: Res<BloomEnabled>,
+) {
+    commands.insert_resource(BulletRenderComponents {
+        mesh: meshes.add(Circle::new(
+            display_properties.shorter_dimension * PLAYER_SIZE,
+        )),
+        material: materials.add(bullet_color(1., bloom.value)),
+        heatmap_material: materials.add(ColorMaterial {
+            color: Color::srgba(1., 0.2, 0.1, HEATMAP_STAMP_ALPHA),
+            alpha_mode: AlphaMode2d::Blend,
+            ..default()
+        }),
+    });
+}
+
+fn letterbox_node(display_properties: &DisplayProperties) -> Node {
+    let side = ((display_properties.w / 2. - display_properties.half_w).max(0.0)).floor();
+    let top = ((display_properties.h / 2. - display_properties.half_h).max(0.0)).floor();
+    Node {
+        position_type: PositionType::Absolute,
+        width: percent(100),
+        height: percent(100),
+        border: UiRect::axes(px(side), px(top)),
+        ..default()
+    }
+}
+
+fn resize_screen_bounds(
+    mut resize_reader: MessageReader<WindowResized>,
+    window: Single<&Window>,
+    mut display_properties: ResMut<DisplayProperties>,
+    (arena_aspect, arena_fraction): (Res<ArenaAspectSetting>, Res<ArenaFraction>),
+    mut letterbox: Query<&mut Node, With<ArenaLetterbox>>,
+    mut scaled_entities: Query<
+        (&mut Transform, Has<Player>),
+        Or<(With<ScreenEdgeBouncer>, With<Player>, With<PlayerAim>)>,
+    >,
+) {
+    // changing the arena aspect or size in the settings rescales everything just like a resize
+    if resize_reader.read().count() > 0 || arena_aspect.is_changed() || arena_fraction.is_changed()
+    {
+        let w = window.resolution.physical_width();
+        let h = window.resolution.physical_height();
+        let previous_shorter_dimension = display_properties.shorter_dimension;
+
+        display_properties.set_size(
+            w as f32,
+            h as f32,
+            arena_aspect.value.ratio(),
+            arena_fraction.value,
+        );
+        for mut node in &mut letterbox {
+            *node = letterbox_node(&display_properties);
+        }
+
+        // keep everything in the arena at the same relative spot instead of leaving it where the old bounds were
+        let ratio = display_properties.shorter_dimension / previous_shorter_dimension;
+        let ps = PLAYER_SIZE * display_properties.shorter_dimension;
+        for (mut transform, is_player) in &mut scaled_entities {
+            transform.translation.x *= ratio;
+            transform.translation.y *= ratio;
+
+            let margin = if is_player { ps } else { 0.0 };
+            let w_margin = (display_properties.half_w - margin).max(0.0);
+            let h_margin = (display_properties.half_h - margin).max(0.0);
+            transform.translation.x = transform.translation.x.clamp(-w_margin, w_margin);
+            transform.translation.y = transform.translation.y.clamp(-h_margin, h_margin);
+        }
+    }
+}
+
+fn handle_screenshake(
+    mut screenshake: ResMut<ScreenshakeIntensity>,
+    mut zoom_punch: ResMut<ZoomPunch>,
+    camera: Single<(&mut Transform, &mut Projection), With<Camera2d>>,
+    time: Res<Time<Real>>,
+    display_properties: Res<DisplayProperties>,
+    reduce_motion: Res<ReduceMotion>,
+    screenshake_config: Res<ScreenshakeConfig>,
+    cinematic: Res<DeathCinematic>,
+    follow: Res<CameraFollow>,
+) {
+    let (mut camera_transform, mut projection) = camera.into_inner();
+    if reduce_motion.value {
+        screenshake.value = 0.0;
+        zoom_punch.value = 0.0;
+    }
+
+    screenshake.value = screenshake.value.lerp(
+        0.0,
+        (time.delta_secs() * screenshake_config.dampening).min(1.0),
+    );
+    let rotation = screenshake_config.velocity * time.elapsed_secs();
+    let dir = Vec2::new(rotation.cos(), rotation.sin());
+    camera_transform.translation = follow.offset.extend(0.0)
+        + Vec3::new(dir.x, dir.y, 0.0) * screenshake.value * display_properties.shorter_dimension;
+
+    // the punch only touches the projection scale, so it layers on top of the translation shake
+    zoom_punch.value = zoom_punch
+        .value
+        .min(ZOOM_PUNCH_MAX)
+        .lerp(0.0, (time.delta_secs() * ZOOM_PUNCH_DAMPENING).min(1.0));
+    if let Projection::Orthographic(orthographic) = projection.as_mut() {
+        orthographic.scale = follow.zoom * (1.0 - zoom_punch.value);
+    }
+
+    // the death framing takes over from the shake as it eases in, so the last moment reads cleanly
+    if cinematic.active {
+        let progress = (cinematic.elapsed / DEATH_CINEMATIC_DURATION).min(1.0);
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+        camera_transform.translation = camera_transform
+            .translation
+            .lerp(cinematic.focus.extend(0.0), eased);
+        if let Projection::Orthographic(orthographic) = projection.as_mut() {
+            orthographic.scale = orthographic.scale.lerp(DEATH_CINEMATIC_ZOOM, eased);
+        }
+    }
+}
+
+// drifts toward the middle of the player and the bullets around it, zooming out just enough that the arena
+// always stays fully on screen with the camera moved by the full reach
+fn follow_action(
+    mut follow: ResMut<CameraFollow>,
+    strength: Res<CameraFollowStrength>,
+    reduce_motion: Res<ReduceMotion>,
+    player: Single<&Transform, With<Player>>,
+    bullets: Query<&Transform, (With<Bullet>, Without<Player>)>,
+    display: Res<DisplayProperties>,
+    time: Res<Time<Real>>,
+) {
+    let strength = if reduce_motion.value {
+        0.0
+    } else {
+        strength.value
+    };
+    let reach = CAMERA_FOLLOW_MAX_OFFSET * display.shorter_dimension * strength;
+    follow.zoom = ((display.half_w + reach) / (display.w / 2.0))
+        .max((display.half_h + reach) / (display.h / 2.0))
+        .max(1.0);
+    let room = Vec2::new(
+        display.w / 2.0 * follow.zoom - display.half_w,
+        display.h / 2.0 * follow.zoom - display.half_h,
+    )
+    .max(Vec2::ZERO);
+
+    let player = player.translation.truncate();
+    let (sum, count) = bullets
+        .iter()
+        .fold((Vec2::ZERO, 0), |(sum, count), bullet| {
+            (sum + bullet.translation.truncate(), count + 1)
+        });
+    let activity = if count > 0 {
+        (player + sum / count as f32) / 2.0
+    } else {
+        player
+    };
+    let target = (activity * strength)
+        .clamp_length_max(reach)
+        .clamp(-room, room);
+    follow.offset = follow
+        .offset
+        .lerp(target, (time.delta_secs() * CAMERA_FOLLOW_RATE).min(1.0));
+}
+
+fn reset_camera_follow(mut follow: ResMut<CameraFollow>) {
+    follow.offset = Vec2::ZERO;
+    follow.zoom = 1.0;
+}
+
+// eases toward the tint of the current state instead of cutting, so a game over fades in its red
+fn tint_background(
+    mut clear_color: ResMut<ClearColor>,
+    game_state: Res<State<AppState>>,
+    background_tint: Res<BackgroundTintEnabled>,
+    time: Res<Time<Real>>,
+    brightness: Res<Brightness>,
+) {
+    let target = if !background_tint.value {
+        Color::BLACK
+    } else {
+        match game_state.get() {
+            AppState::InGame | AppState::Paused => GAME_BACKGROUND_TINT,
+            AppState::GameOver => GAME_OVER_BACKGROUND_TINT,
+            _ => MENU_BACKGROUND_TINT,
+        }
+    };
+    let mut lifted = Hsva::from(target);
+    lifted.value += (brightness.value - 1.0) * BRIGHTNESS_BACKGROUND_LIFT;
+    let current = clear_color.0.to_linear();
+    let blend = (time.delta_secs() * BACKGROUND_TINT_RATE).min(1.0);
+    clear_color.0 = current.mix(&LinearRgba::from(lifted), blend).into();
+}
+
+fn reset_camera_feedback(
+    mut screenshake: ResMut<ScreenshakeIntensity>,
+    mut zoom_punch: ResMut<ZoomPunch>,
+    mut screen_flash: ResMut<ScreenFlash>,
+    mut cinematic: ResMut<DeathCinematic>,
+    mut camera: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
+) {
+    screenshake.value = 0.0;
+    zoom_punch.value = 0.0;
+    screen_flash.intensity = 0.0;
+    cinematic.active = false;
+    // handle_screenshake doesn't run in the menu, so the camera has to be put back here
+    for (mut transform, mut projection) in &mut camera {
+        transform.translation = Vec3::ZERO;
+        if let Projection::Orthographic(orthographic) = projection.as_mut() {
+            orthographic.scale = 1.0;
+        }
+    }
+}
+
+fn handle_screen_flash(
+    mut screen_flash: ResMut<ScreenFlash>,
+    mut overlay: Single<&mut BackgroundColor, With<ScreenFlashOverlay>>,
+    time: Res<Time<Real>>,
+    reduce_motion: Res<ReduceMotion>,
+) {
+    if reduce_motion.value {
+        screen_flash.intensity = 0.0;
+    }
+    screen_flash.intensity = screen_flash
+        .intensity
+        .lerp(0.0, (time.delta_secs() * SCREEN_FLASH_DAMPENING).min(1.0));
+    overlay.0 = screen_flash
+        .color
+        .with_alpha(screen_flash.intensity.clamp(0.0, 1.0));
+}
+
+fn reset_score(mut score: ResMut<Score>, mut score_attack: ResMut<ScoreAttack>) {
+    score.value = 0.;
+    score_attack.points = 0.;
+}
+
+fn reset_intensity(mut intensity: ResMut<Intensity>, mut bullet_growth: ResMut<BulletGrowth>) {
+    intensity.survival_time = 0.;
+    intensity.value = 0.;
+    intensity.sudden_death = 0.;
+    bullet_growth.value = 1.;
+}
+
+fn update_intensity(
+    mut intensity: ResMut<Intensity>,
+    curve: Res<IntensityCurve>,
+    sudden_death: Res<SuddenDeathConfig>,
+    time: Res<Time<Virtual>>,
+) {
+    intensity.survival_time += time.delta_secs();
+    intensity.value = curve.sample(intensity.survival_time);
+    intensity.sudden_death = sudden_death.sample(intensity.survival_time);
+}
+
+// fades out over the first seconds of sudden death, only spawned while a threshold is set
+fn show_sudden_death_warning(
+    intensity: Res<Intensity>,
+    sudden_death: Res<SuddenDeathConfig>,
+    warning: Single<(&mut Visibility, &mut TextColor), With<SuddenDeathWarning>>,
+) {
+    let (mut visibility, mut color) = warning.into_inner();
+    let since_start = intensity.survival_time - sudden_death.threshold;
+    if since_start < 0.0 || since_start > SUDDEN_DEATH_WARNING_DURATION {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+
+    visibility.set_if_neq(Visibility::Inherited);
+    color.0 =
+        SUDDEN_DEATH_WARNING_COLOR.with_alpha(1.0 - since_start / SUDDEN_DEATH_WARNING_DURATION);
+}
+
+fn update_bullet_growth(
+    intensity: Res<Intensity>,
+    growing_bullets: Res<GrowingBulletsEnabled>,
+    mut bullet_growth: ResMut<BulletGrowth>,
+) {
+    bullet_growth.value = if growing_bullets.value {
+        1.0 + intensity.value * INTENSITY_SIZE_BONUS
+    } else {
+        1.0
+    };
+}
+
+fn handle_score(
+    mut time: ResMut<Time<Virtual>>,
+    mut score: ResMut<Score>,
+    mut game_state: ResMut<NextState<AppState>>,
+    game_mode: Res<SelectedGameMode>,
+    display: Query<&mut Text, With<ScoreDisplay>>,
+    max_run: Res<MaxRunSeconds>,
+    cap_display: Query<&mut Text, (With<RunCapDisplay>, Without<ScoreDisplay>)>,
+    precision: Res<TimerPrecisionSetting>,
+    mut score_attack: ResMut<ScoreAttack>,
+) {
+    score.value += time.delta_secs();
+
+    // unlike the timed goal this is no win, the run just ends cleanly on the game over screen
+    if max_run.value > 0.0 {
+        if score.value >= max_run.value {
+            time.pause();
+            game_state.set(AppState::GameOver);
+        }
+        for mut text in cap_display {
+            text.0.clear();
+            text.0.push_str("Cap ");
+            write_time_text(
+                &mut text.0,
+                (max_run.value - score.value).max(0.0),
+                precision.value,
+            );
+        }
+    }
+
+    // in timed mode the clock counts down towards the win condition instead of up
+    let displayed_time = match game_mode.value {
+        GameMode::Endless | GameMode::Survival => score.value,
+        GameMode::Timed => {
+            if score.value >= TIMED_MODE_DURATION {
+                time.pause();
+                game_state.set(AppState::Victory);
+            }
+            (TIMED_MODE_DURATION - score.value).max(0.0)
+        }
+        // the bounce points come in from the fixed step, only the trickle for surviving is added here
+        GameMode::ScoreAttack => {
+            score_attack.points += time.delta_secs() * SCORE_ATTACK_POINTS_PER_SECOND;
+            for mut text in display.into_iter() {
+                text.0.clear();
+                let _ = write!(text.0, "{}", score_attack.points as u32);
+            }
+            return;
+        }
+    };
+
+    // written straight into the existing buffers, the clock changes every single frame
+    for mut text in display.into_iter() {
+        text.0.clear();
+        write_time_text(&mut text.0, displayed_time, precision.value);
+    }
+}
+
+fn update_progress_bar(
+    score: Res<Score>,
+    game_mode: Res<SelectedGameMode>,
+    best_ghost: Res<BestGhost>,
+    bars: Query<(&mut Node, &mut BackgroundColor), With<ProgressBar>>,
+) {
+    let target = match game_mode.value {
+        GameMode::Endless => best_ghost.duration,
+        GameMode::Timed => TIMED_MODE_DURATION,
+        GameMode::Survival | GameMode::ScoreAttack => return,
+    };
+    if target <= 0.0 {
+        return;
+    }
+    let progress = score.value / target;
+    // the timed goal ends the run, so only a beaten endless best can push past full
+    let color = if game_mode.value == GameMode::Endless && progress > 1.0 {
+        PROGRESS_BAR_BEATEN_COLOR
+    } else {
+        PROGRESS_BAR_COLOR
+    };
+
+    for (mut node, mut background) in bars {
+        node.width = percent(progress.clamp(0.0, 1.0) * 100.0);
+        background.0 = color;
+    }
+}
+
+fn convert_time_to_text(time: f32, precision: TimerPrecision) -> String {
+    let mut time_text = String::new();
+    write_time_text(&mut time_text, time, precision);
+    time_text
+}
+
+// appends MM:SS:CC or MM:SS:MMM, the fraction is truncated so the clock never shows a time not yet reached
+fn write_time_text(text: &mut String, time: f32, precision: TimerPrecision) {
+    let (units_per_second, digits) = match precision {
+        TimerPrecision::Centi => (100, 2),
+        TimerPrecision::Milli => (1000, 3),
+    };
+    let units = (time.max(0.0) * units_per_second as f32) as u32;
+    let s = units / units_per_second;
+    let m = s / 60;
+
+    let _ = write!(
+        text,
+        "{:02}:{:02}:{:0digits$}",
+        m,
+        s % 60,
+        units % units_per_second,
+        digits = digits
+    );
+}
+
+fn make_mouse_visible(mut cursor_options: Single<&mut bevy::window::CursorOptions>) {
+    cursor_options.visible = true;
+}
+// absolute aim follows the cursor itself, so it has to stay visible to aim with
+fn apply_in_game_cursor(
+    mut cursor_options: Single<&mut bevy::window::CursorOptions>,
+    aim_mode: Res<AimModeSetting>,
+) {
+    cursor_options.visible = aim_mode.value == AimMode::Absolute;
+}
+
+// alt-tabbing out of a run must not leave the cursor hidden over other windows
+fn restore_cursor_on_focus_change(
+    mut focus_reader: MessageReader<WindowFocused>,
+    mut cursor_options: Single<&mut bevy::window::CursorOptions>,
+    aim_mode: Res<AimModeSetting>,
+    game_state: Res<State<AppState>>,
+) {
+    for focus in focus_reader.read() {
+        cursor_options.visible = !focus.focused
+            || *game_state.get() != AppState::InGame
+            || aim_mode.value == AimMode::Absolute;
+    }
+}
+
+fn handle_trail_particles(
+    mut batch: ResMut<ParticleBatch>,
+    time: Res<Time<Virtual>>,
+    reduce_motion: Res<ReduceMotion>,
+) {
+    let fade_rate = if reduce_motion.value {
+        REDUCED_MOTION_TRAIL_FADE_RATE
+    } else {
+        1.0
+    };
+    batch.trail.retain_mut(|particle| {
+        particle.lifetime -= time.delta_secs() * fade_rate;
+        particle.lifetime >= 0.0
+    });
+}
+
+fn spawn_particle_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    bullet_data: Res<BulletRenderComponents>,
+) {
+    // the mesh bounds never follow the rebuilt vertices, so culling is left out entirely
+    commands.spawn((
+        TrailParticleMesh,
+        Mesh2d(meshes.add(empty_particle_mesh())),
+        // trails keep the color a bullet had when they were dropped, which lives in the vertex colors
+        MeshMaterial2d(materials.add(ColorMaterial {
+            color: Color::WHITE,
+            alpha_mode: AlphaMode2d::Blend,
+            ..default()
+        })),
+        Transform::from_xyz(0.0, 0.0, TRAIL_LAYER),
+        Visibility::Hidden,
+        NoFrustumCulling,
+    ));
+    commands.spawn((
+        BounceParticleMesh,
+        Mesh2d(meshes.add(empty_particle_mesh())),
+        MeshMaterial2d(bullet_data.material.clone()),
+        Transform::from_xyz(0.0, 0.0, BOUNCE_PARTICLE_LAYER),
+        Visibility::Hidden,
+        NoFrustumCulling,
+    ));
+    commands.spawn((
+        RibbonTrailMesh,
+        Mesh2d(meshes.add(empty_particle_mesh())),
+        MeshMaterial2d(materials.add(ColorMaterial {
+            color: Color::WHITE,
+            alpha_mode: AlphaMode2d::Blend,
+            ..default()
+        })),
+        Transform::from_xyz(0.0, 0.0, TRAIL_LAYER),
+        Visibility::Hidden,
+        NoFrustumCulling,
+    ));
+    commands.spawn((
+        WallRippleMesh,
+        Mesh2d(meshes.add(empty_particle_mesh())),
+        MeshMaterial2d(materials.add(ColorMaterial {
+            color: Color::WHITE,
+            alpha_mode: AlphaMode2d::Blend,
+            ..default()
+        })),
+        Transform::from_xyz(0.0, 0.0, BOUNCE_PARTICLE_LAYER),
+        Visibility::Hidden,
+        NoFrustumCulling,
+    ));
+}
+
+// one strip per bullet from where it is now back through its sampled points, narrowing and fading toward the tail
+// every bullet shares a single mesh, the same way the particle batches do
+fn draw_ribbon_trails(
+    bullets: Query<(&Transform, &RibbonTrail)>,
+    ribbon_mesh: Single<(&Mesh2d, &mut Visibility), With<RibbonTrailMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    display_properties: Res<DisplayProperties>,
+    bullet_data: Res<BulletRenderComponents>,
+    materials: Res<Assets<ColorMaterial>>,
+    mut strip: Local<Vec<Vec2>>,
+) {
+    let (mesh_handle, mut visibility) = ribbon_mesh.into_inner();
+    if bullets.iter().all(|(_, ribbon)| ribbon.points.is_empty()) {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+    visibility.set_if_neq(Visibility::Inherited);
+    let Some(mesh) = meshes.get_mut(mesh_handle.id()) else {
+        return;
+    };
+
+    let color = materials
+        .get(bullet_data.material.id())
+        .map(|mat| mat.color)
+        .unwrap_or(Color::WHITE);
+    let half_width = display_properties.shorter_dimension * PLAYER_SIZE * 0.5;
+
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut colors = Vec::new();
+    let mut indices = Vec::new();
+    for (transform, ribbon) in &bullets {
+        strip.clear();
+        strip.push(transform.translation.truncate());
+        strip.extend(ribbon.points.iter().copied());
+        if strip.len() < 2 {
+            continue;
+        }
+
+        let first = positions.len() as u32;
+        let last = strip.len() - 1;
+        for (i, point) in strip.iter().enumerate() {
+            let progress = i as f32 / last as f32;
+            let direction =
+                (strip[i.saturating_sub(1)] - strip[(i + 1).min(last)]).normalize_or_zero();
+            let side = direction.perp() * half_width * (1.0 - progress);
+            let linear = color
+                .with_alpha(color.alpha() * (1.0 - progress))
+                .to_linear();
+            let vertex_color = [linear.red, linear.green, linear.blue, linear.alpha];
+            for (offset, v) in [(side, 0.0), (-side, 1.0)] {
+                positions.push([point.x + offset.x, point.y + offset.y, 0.0]);
+                uvs.push([progress, v]);
+                colors.push(vertex_color);
+            }
+        }
+        for i in 0..last as u32 {
+            let a = first + i * 2;
+            indices.extend_from_slice(&[a, a + 1, a + 2, a + 1, a + 3, a + 2]);
+        }
+    }
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+}
+
+fn empty_particle_mesh() -> Mesh {
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+}
+
+// writes one circle per particle into the mesh, laid out like the regular circle mesh
+fn fill_particle_mesh(mesh: &mut Mesh, circles: impl ExactSizeIterator<Item = (Vec2, f32, Color)>) {
+    let vertex_count = circles.len() * PARTICLE_MESH_RESOLUTION;
+    let mut positions = Vec::with_capacity(vertex_count);
+    let mut uvs = Vec::with_capacity(vertex_count);
+    let mut colors = Vec::with_capacity(vertex_count);
+    let mut indices = Vec::with_capacity(circles.len() * (PARTICLE_MESH_RESOLUTION - 2) * 3);
+
+    for (center, radius, color) in circles {
+        let first = positions.len() as u32;
+        let linear = color.to_linear();
+        let color = [linear.red, linear.green, linear.blue, linear.alpha];
+        for i in 0..PARTICLE_MESH_RESOLUTION {
+            let angle = PI / 2.0 + i as f32 * 2.0 * PI / PARTICLE_MESH_RESOLUTION as f32;
+            let (sin, cos) = angle.sin_cos();
+            positions.push([center.x + cos * radius, center.y + sin * radius, 0.0]);
+            uvs.push([0.5 * (cos + 1.0), 1.0 - 0.5 * (sin + 1.0)]);
+            colors.push(color);
+        }
+        for i in 1..(PARTICLE_MESH_RESOLUTION as u32 - 1) {
+            indices.extend_from_slice(&[first, first + i, first + i + 1]);
+        }
+    }
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+}
+
+fn draw_particle_batches(
+    batch: Res<ParticleBatch>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    display_properties: Res<DisplayProperties>,
+    bullet_data: Res<BulletRenderComponents>,
+    trail_mesh: Single<(&Mesh2d, &mut Visibility), With<TrailParticleMesh>>,
+    bounce_mesh: Single<
+        (&Mesh2d, &mut MeshMaterial2d<ColorMaterial>, &mut Visibility),
+        (With<BounceParticleMesh>, Without<TrailParticleMesh>),
+    >,
+    ripple_mesh: Single<
+        (&Mesh2d, &mut Visibility),
+        (
+            With<WallRippleMesh>,
+            Without<TrailParticleMesh>,
+            Without<BounceParticleMesh>,
+        ),
+    >,
+) {
+    let radius = display_properties.shorter_dimension * PLAYER_SIZE;
+
+    let (trail_handle, mut trail_visibility) = trail_mesh.into_inner();
+    // an empty vertex buffer is never worth sending to the gpu
+    *trail_visibility = if batch.trail.is_empty() {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    if let Some(mesh) = meshes.get_mut(trail_handle.id()) {
+        fill_particle_mesh(
+            mesh,
+            batch.trail.iter().map(|particle| {
+                let progress = particle.lifetime / particle.max_lifetime;
+                (
+                    particle.position,
+                    radius * 0.0.lerp(0.5, progress),
+                    particle
+                        .base_color
+                        .with_alpha(particle.base_color.alpha() * progress),
+                )
+            }),
+        );
+    }
+
+    // bounce particles share the oscillating bullet material, which gets replaced whenever the bullet data is rebuilt
+    let (bounce_handle, mut bounce_material, mut bounce_visibility) = bounce_mesh.into_inner();
+    if bounce_material.0 != bullet_data.material {
+        bounce_material.0 = bullet_data.material.clone();
+    }
+    *bounce_visibility = if batch.bounce.is_empty() {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    if let Some(mesh) = meshes.get_mut(bounce_handle.id()) {
+        fill_particle_mesh(
+            mesh,
+            batch.bounce.iter().map(|particle| {
+                let scale =
+                    ((PI / 2.0).lerp(0.0, particle.lifetime / TRAIL_PARTICLE_LIFETIME)).cos() * 0.5;
+                (particle.position, radius * scale, Color::WHITE)
+            }),
+        );
+    }
+
+    let (ripple_handle, mut ripple_visibility) = ripple_mesh.into_inner();
+    *ripple_visibility = if batch.ripple.is_empty() {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    if let Some(mesh) = meshes.get_mut(ripple_handle.id()) {
+        let dots: Vec<(Vec2, f32, Color)> = batch
+            .ripple
+            .iter()
+            .flat_map(|ripple| {
+                let progress = 1.0 - ripple.lifetime / WALL_RIPPLE_LIFETIME;
+                let spread = display_properties.shorter_dimension
+                    * WALL_RIPPLE_SPREAD
+                    * (1.0 - (1.0 - progress) * (1.0 - progress));
+                let tangent = ripple.normal.perp();
+                let color =
+                    WALL_RIPPLE_COLOR.with_alpha(WALL_RIPPLE_COLOR.alpha() * (1.0 - progress));
+                (0..ripple.dots).map(move |i| {
+                    // -1 to 1 along the wall, the middle dot leaning furthest into the arena
+                    let t = if ripple.dots > 1 {
+                        i as f32 / (ripple.dots - 1) as f32 * 2.0 - 1.0
+                    } else {
+                        0.0
+                    };
+                    let offset = tangent * t * spread
+                        + ripple.normal * (1.0 - t * t) * spread * WALL_RIPPLE_BULGE;
+                    (
+                        ripple.position + offset,
+                        radius * 0.25 * (1.0 - progress),
+                        color,
+                    )
+                })
+            })
+            .collect();
+        fill_particle_mesh(mesh, dots.into_iter());
+    }
+}
+
+fn update_trail_settings(
+    particle_quality: Res<ParticleQualitySetting>,
+    mut trail_settings: ResMut<TrailSettings>,
+) {
+    if !particle_quality.is_changed() {
+        return;
+    }
+
+    *trail_settings = particle_quality.value.trail_settings();
+}
+
+fn spawn_threat_indicators(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    display_properties: Res<DisplayProperties>,
+    enabled: Res<ThreatIndicatorsEnabled>,
+) {
+    if !enabled.value {
+        return;
+    }
+
+    let size = THREAT_INDICATOR_SIZE * display_properties.shorter_dimension;
+    // points along +x, rotated toward its bullet every frame
+    let mesh = meshes.add(Triangle2d::new(
+        vec2(size, 0.0),
+        vec2(-size, size * 0.7),
+        vec2(-size, -size * 0.7),
+    ));
+    for _ in 0..THREAT_INDICATOR_COUNT {
+        commands.spawn((
+            ThreatIndicator,
+            Mesh2d(mesh.clone()),
+            // every arrow fades on its own, so they can't share a material
+            MeshMaterial2d(materials.add(ColorMaterial {
+                color: Color::WHITE,
+                alpha_mode: AlphaMode2d::Blend,
+                ..default()
+            })),
+            Transform::from_xyz(0.0, 0.0, THREAT_INDICATOR_LAYER),
+            Visibility::Hidden,
+            DespawnOnExit(AppState::InGame),
+        ));
+    }
+}
+
+fn spawn_speed_lines(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    display_properties: Res<DisplayProperties>,
+) {
+    // lies along +x, rotated to point at the center
+    let mesh = meshes.add(Rectangle::new(
+        SPEED_LINE_LENGTH * display_properties.shorter_dimension,
+        SPEED_LINE_THICKNESS * display_properties.shorter_dimension,
+    ));
+    let material = materials.add(ColorMaterial {
+        color: Color::WHITE.with_alpha(0.0),
+        alpha_mode: AlphaMode2d::Blend,
+        ..default()
+    });
+    for i in 0..SPEED_LINE_COUNT {
+        commands.spawn((
+            SpeedLine {
+                angle: i as f32 / SPEED_LINE_COUNT as f32 * 2.0 * PI,
+                // golden ratio steps scatter the phases without touching the run's random source
+                phase: (i as f32 * 0.618_034).fract(),
+            },
+            Mesh2d(mesh.clone()),
+            MeshMaterial2d(material.clone()),
+            Transform::from_xyz(0.0, 0.0, SPEED_LINE_LAYER),
+            Visibility::Hidden,
+            DespawnOnExit(AppState::InGame),
+        ));
+    }
+    commands.insert_resource(SpeedLineMaterial { handle: material });
+}
+
+// nothing in the game slows time or speeds the player up by default, so the lines only show up with a
+// movement intensity bonus from the config file or once something changes the virtual clock speed
+fn animate_speed_lines(
+    mut lines: Query<(&SpeedLine, &mut Transform, &mut Visibility)>,
+    material: Res<SpeedLineMaterial>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    display_properties: Res<DisplayProperties>,
+    (movement, intensity): (Res<MovementConfig>, Res<Intensity>),
+    reduce_motion: Res<ReduceMotion>,
+    virtual_time: Res<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+) {
+    let slow_motion = (1.0 - virtual_time.relative_speed()).clamp(0.0, 1.0);
+    let speedup = if movement.speed > 0.0 {
+        (movement.speed_at(&intensity) / movement.speed - 1.0).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let strength = if reduce_motion.value || virtual_time.is_paused() {
+        0.0
+    } else {
+        slow_motion.max(speedup)
+    };
+
+    if let Some(mat) = materials.get_mut(material.handle.id()) {
+        mat.color = Color::WHITE.with_alpha(strength * SPEED_LINE_MAX_ALPHA);
+    }
+
+    // from the screen corner inward, so the lines cover the edges of any aspect ratio
+    let outer = Vec2::new(display_properties.half_w, display_properties.half_h).length();
+    let travel = SPEED_LINE_TRAVEL * display_properties.shorter_dimension;
+    for (line, mut transform, mut visibility) in &mut lines {
+        visibility.set_if_neq(if strength > 0.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        if strength <= 0.0 {
+            continue;
+        }
+
+        let progress = (real_time.elapsed_secs() * SPEED_LINE_SPEED + line.phase).fract();
+        let direction = Vec2::from_angle(line.angle);
+        transform.translation = (direction * (outer - progress * travel)).extend(SPEED_LINE_LAYER);
+        transform.rotation = Quat::from_rotation_z(line.angle);
+    }
+}
+
+// gizmos are drawn over every mesh, so the lines stay visible even in a dense crowd
+fn draw_velocity_vectors(
+    mut gizmos: Gizmos,
+    bullets: Query<(&Transform, &ScreenEdgeBouncer), With<Bullet>>,
+    enabled: Res<VelocityVectorsEnabled>,
+    display_properties: Res<DisplayProperties>,
+    intensity: Res<Intensity>,
+    assist_mode: Res<AssistMode>,
+    sudden_death: Res<SuddenDeathConfig>,
+) {
+    if !enabled.value {
+        return;
+    }
+
+    // the same speed move_bouncers applies, so the line ends where the bullet is headed
+    let scale = BULLET_MOVEMENT_SPEED_NORMALIZED
+        * display_properties.shorter_dimension
+        * bullet_speed_multiplier(&intensity, &assist_mode, &sudden_death)
+        * VELOCITY_VECTOR_SECONDS;
+    for (transform, bouncer) in &bullets {
+        let start = transform.translation.truncate();
+        gizmos.line_2d(
+            start,
+            start + bouncer.velocity.truncate() * scale,
+            VELOCITY_VECTOR_COLOR,
+        );
+    }
+}
+
+// where a ray from `origin` along `direction` leaves a box of the given half extents
+fn ray_to_box_edge(origin: Vec2, direction: Vec2, half_extents: Vec2) -> Vec2 {
+    let mut distance = f32::MAX;
+    if direction.x != 0.0 {
+        distance = distance.min((half_extents.x * direction.x.signum() - origin.x) / direction.x);
+    }
+    if direction.y != 0.0 {
+        distance = distance.min((half_extents.y * direction.y.signum() - origin.y) / direction.y);
+    }
+    origin + direction * distance.max(0.0)
+}
+
+fn update_threat_indicators(
+    mut indicators: Query<
+        (
+            &mut Transform,
+            &mut Visibility,
+            &MeshMaterial2d<ColorMaterial>,
+        ),
+        With<ThreatIndicator>,
+    >,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    bullets: Query<&Transform, (With<Bullet>, Without<ThreatIndicator>)>,
+    player: Single<&Transform, (With<Player>, Without<ThreatIndicator>)>,
+    display_properties: Res<DisplayProperties>,
+) {
+    let range = THREAT_INDICATOR_RANGE * display_properties.shorter_dimension;
+    let player_position = player.translation.truncate();
+
+    let mut threats: Vec<(f32, Vec2)> = bullets
+        .iter()
+        .map(|bullet| bullet.translation.truncate() - player_position)
+        .map(|offset| (offset.length(), offset))
+        .filter(|(distance, _)| *distance < range)
+        .collect();
+    threats.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let margin = THREAT_INDICATOR_MARGIN * display_properties.shorter_dimension;
+    let half_extents = vec2(
+        display_properties.half_w - margin,
+        display_properties.half_h - margin,
+    );
+    let mut threats = threats.into_iter();
+    for (mut transform, mut visibility, material) in &mut indicators {
+        let Some((distance, offset)) = threats.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let Some(direction) = offset.try_normalize() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Visible;
+        let edge = ray_to_box_edge(player_position, direction, half_extents);
+        transform.translation = edge.extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(direction.to_angle());
+
+        if let Some(mat) = materials.get_mut(material.id()) {
+            mat.color = Color::WHITE.with_alpha(1.0 - distance / range);
+        }
+    }
+}
+
+// applies the bullet growth, plus the danger pulse on top of it
+fn scale_bullets(
+    mut bullets: Query<(&mut Transform, &ScreenEdgeBouncer), With<Bullet>>,
+    player: Single<&Transform, (With<Player>, Without<Bullet>)>,
+    display_properties: Res<DisplayProperties>,
+    danger_highlight: Res<DangerHighlightEnabled>,
+    (hitbox_scale, intensity, reduce_motion): (Res<HitboxScale>, Res<Intensity>, Res<ReduceMotion>),
+    bullet_growth: Res<BulletGrowth>,
+    time: Res<Time<Virtual>>,
+    assist_mode: Res<AssistMode>,
+    sudden_death: Res<SuddenDeathConfig>,
+) {
+    let check_radius = DANGER_CHECK_RADIUS * display_properties.shorter_dimension;
+    let hit_radius = player_collision_distance(
+        &display_properties,
+        &hitbox_scale,
+        &bullet_growth,
+        &assist_mode,
+    );
+    let speed = BULLET_MOVEMENT_SPEED_NORMALIZED
+        * display_properties.shorter_dimension
+        * bullet_speed_multiplier(&intensity, &assist_mode, &sudden_death);
+    // a steady enlargement instead of a pulse for motion sensitive players
+    let pulse = if reduce_motion.value {
+        1.0
+    } else {
+        (time.elapsed_secs() * DANGER_PULSE_SPEED).sin() * 0.5 + 0.5
+    };
+
+    for (mut transform, bouncer) in &mut bullets {
+        let offset = (transform.translation - player.translation).truncate();
+        let velocity = bouncer.velocity.truncate() * speed;
+
+        // projects nearby bullets along their current velocity, ignoring bounces, and pulses the ones headed into the player
+        let mut dangerous = false;
+        if danger_highlight.value && offset.length() < check_radius {
+            // time of the closest approach, limited to the lookahead window
+            let closest_time = if velocity.length_squared() > 0.0 {
+                (-offset.dot(velocity) / velocity.length_squared()).clamp(0.0, DANGER_LOOKAHEAD)
+            } else {
+                0.0
+            };
+            dangerous = (offset + velocity * closest_time).length() < hit_radius;
+        }
+
+        transform.scale = if dangerous {
+            Vec3::splat(bullet_growth.value * (1.0 + DANGER_PULSE_SCALE * pulse))
+        } else {
+            Vec3::splat(bullet_growth.value)
+        };
+    }
+}
+
+// the stamps share one translucent material, so overlapping ones build up in bullet-dense areas
+fn stamp_heatmap(
+    mut commands: Commands,
+    bullet_data: Res<BulletRenderComponents>,
+    bullets: Query<&Transform, With<Bullet>>,
+    mut stamps: Query<&mut Transform, (With<HeatmapStamp>, Without<Bullet>)>,
+    heatmap: Res<HeatmapEnabled>,
+    mut stamper: ResMut<HeatmapStamper>,
+    time: Res<Time<Virtual>>,
+) {
+    if !heatmap.value {
+        return;
+    }
+
+    stamper.timer.tick(time.delta());
+    if !stamper.timer.just_finished() {
+        return;
+    }
+
+    for transform in &bullets {
+        let stamp_transform =
+            Transform::from_translation(transform.translation.with_z(HEATMAP_LAYER))
+                .with_scale(Vec3::splat(HEATMAP_STAMP_SCALE));
+        // a stamp that is already gone is just dropped from the ring, a fresh one takes its place
+        if stamper.stamps.len() >= HEATMAP_MAX_STAMPS
+            && let Some(oldest) = stamper.stamps.pop_front()
+            && let Ok(mut oldest_transform) = stamps.get_mut(oldest)
+        {
+            *oldest_transform = stamp_transform;
+            stamper.stamps.push_back(oldest);
+            continue;
+        }
+        let stamp = commands
+            .spawn((
+                HeatmapStamp,
+                Mesh2d(bullet_data.mesh.clone()),
+                MeshMaterial2d(bullet_data.heatmap_material.clone()),
+                stamp_transform,
+                DespawnOnEnter(AppState::Menu),
+            ))
+            .id();
+        stamper.stamps.push_back(stamp);
+    }
+}
+
+// the stamps themselves go away with the menu
+fn reset_heatmap_stamper(mut stamper: ResMut<HeatmapStamper>) {
+    stamper.stamps.clear();
+}
+
+fn spawn_bullet_trail(
+    mut batch: ResMut<ParticleBatch>,
+    bullet_data: Res<BulletRenderComponents>,
+    materials: Res<Assets<ColorMaterial>>,
+    bullets: Query<(&Transform, &mut TrailParticleSpawner, &mut RibbonTrail), With<Bullet>>,
+    time: Res<Time<Virtual>>,
+    trail_settings: Res<TrailSettings>,
+    ribbon_trails: Res<RibbonTrailsEnabled>,
+) {
+    // as many points as dots would be alive at once, so both styles reach equally far back
+    let ribbon_length = (trail_settings.lifetime / trail_settings.interval).ceil() as usize;
+    for (transform, mut spawner, mut ribbon) in bullets {
+        spawner.timer.tick(time.delta());
+
+        if !spawner.timer.just_finished() {
+            continue;
+        }
+
+        if ribbon_trails.value {
+            ribbon.points.push_front(transform.translation.truncate());
+            ribbon.points.truncate(ribbon_length);
+            continue;
+        }
+
+        // every trail particle keeps its own color so it can fade out independently of the shared bullet color
+        let base_color = materials
+            .get(bullet_data.material.id())
+            .map(|mat| mat.color)
+            .unwrap_or(Color::WHITE);
+
+        batch.trail.push(TrailParticle {
+            position: transform.translation.truncate(),
+            lifetime: trail_settings.lifetime,
+            max_lifetime: trail_settings.lifetime,
+            base_color,
+        });
+    }
+}
+
+// the spawner only runs while the player moves, and faster the closer the input is to full speed
+fn spawn_player_trail(
+    mut batch: ResMut<ParticleBatch>,
+    player: Single<(&Transform, &mut TrailParticleSpawner), With<Player>>,
+    input: Res<InputState>,
+    time: Res<Time<Virtual>>,
+    trail_settings: Res<TrailSettings>,
+) {
+    let (transform, mut spawner) = player.into_inner();
+    let speed = input.movement.length().min(1.0);
+    if speed < GAMEPAD_STICK_DEADZONE {
+        return;
+    }
+
+    spawner.timer.tick(time.delta().mul_f32(speed));
+    if !spawner.timer.just_finished() {
+        return;
+    }
+
+    batch.trail.push(TrailParticle {
+        position: transform.translation.truncate(),
+        lifetime: trail_settings.lifetime,
+        max_lifetime: trail_settings.lifetime,
+        base_color: PLAYER_TRAIL_COLOR,
+    });
+}
+
+fn spawn_bullet(
+    mut commands: Commands,
+    bullet_data: Res<BulletRenderComponents>,
+    mut timer: Single<&mut Player, With<Player>>,
+    player: Single<&Transform, With<Player>>,
+    aim: Single<&Transform, With<PlayerAim>>,
+    time: Res<Time<Virtual>>,
+    display_properties: Res<DisplayProperties>,
+    (mut screenshake, screenshake_config): (ResMut<ScreenshakeIntensity>, Res<ScreenshakeConfig>),
+    mut zoom_punch: ResMut<ZoomPunch>,
+    asset_server: Res<AssetServer>,
+    mut rumble: Rumble,
+    (intensity, game_mode, sudden_death): (
+        Res<Intensity>,
+        Res<SelectedGameMode>,
+        Res<SuddenDeathConfig>,
+    ),
+    trail_settings: Res<TrailSettings>,
+    (hitbox_scale, bullet_growth, assist_mode): (
+        Res<HitboxScale>,
+        Res<BulletGrowth>,
+        Res<AssistMode>,
+    ),
+    fire_input: FireInput,
+    (shot_pattern, mut randomness): (Res<ShotPattern>, ResMut<RandomSource>),
+) {
+    if let Some(since_shot) = &mut timer.since_shot {
+        *since_shot += time.delta_secs();
+    }
+    if fire_input.tapped()
+        && timer
+            .since_shot
+            .is_some_and(|since_shot| since_shot >= TAP_FIRE_MIN_INTERVAL)
+    {
+        timer.bullet_timer = 0.0;
+    }
+
+    if !fire_input.held() {
+        return;
+    }
+
+    timer.bullet_timer -= time.delta_secs();
+
+    if timer.bullet_timer > 0.0 {
+        return;
+    }
+
+    let aim_direction = shot_direction(player.translation, aim.translation);
+    let mut spawn_distance = player_collision_distance(
+        &display_properties,
+        &hitbox_scale,
+        &bullet_growth,
+        &assist_mode,
+    );
+    let pellet_angles = shot_pattern.pellet_angles();
+    if let [first, second, ..] = pellet_angles.as_slice() {
+        // far enough out that even the closest jittered neighbours don't start inside each other
+        let closest_gap = (second - first).abs() * (1.0 - 2.0 * SHOT_SPREAD_JITTER);
+        let pellet_distance =
+            PLAYER_SIZE * bullet_growth.value * display_properties.shorter_dimension
+                / (closest_gap * 0.5).sin().max(f32::EPSILON);
+        spawn_distance = spawn_distance.max(pellet_distance);
+    }
+    // keeps neighbouring pellets from swapping places, a lone bullet flies straight
+    let jitter = match pellet_angles.as_slice() {
+        [first, second, ..] => (second - first).abs() * SHOT_SPREAD_JITTER,
+        _ => 0.0,
+    };
+    for angle in pellet_angles {
+        let angle = if jitter > 0.0 {
+            angle + randomness.0.random_range(-jitter..jitter)
+        } else {
+            angle
+        };
+        let initial_velocity = Quat::from_rotation_z(angle) * aim_direction;
+        let initial_position = bullet_spawn_position(
+            player.translation,
+            initial_velocity,
+            spawn_distance,
+            display_properties.shorter_dimension,
+        );
+
+        commands.spawn(bullet_bundle(
+            &bullet_data,
+            &trail_settings,
+            initial_position,
+            initial_velocity,
+        ));
+    }
+    commands.spawn((
+        AudioPlayer::new(asset_server.load("Boom29.wav")),
+        PlaybackSettings::DESPAWN,
+    ));
+    screenshake.value += screenshake_config.kick(screenshake_config.on_shoot);
+    zoom_punch.value += ZOOM_PUNCH_ON_SHOOT;
+
+    rumble.add(100, 0.1, 0.3);
+
+    let slowest_fire_interval = match game_mode.value {
+        GameMode::Endless | GameMode::Survival | GameMode::ScoreAttack => {
+            ENDLESS_SLOWEST_FIRE_INTERVAL
+        }
+        GameMode::Timed => TIMED_SLOWEST_FIRE_INTERVAL,
+    };
+    timer.bullet_timer += 0.05.lerp(slowest_fire_interval, intensity.value)
+        / (1.0 + intensity.sudden_death * sudden_death.fire_rate_bonus);
+    timer.since_shot = Some(0.0);
+}
+
+// grown bullets and a large hitbox could otherwise reach past the regular offset and kill on the spot
+fn bullet_spawn_position(
+    player: Vec3,
+    direction: Vec3,
+    collision_distance: f32,
+    shorter_dimension: f32,
+) -> Vec3 {
+    let offset = (BULLET_SPAWN_OFFSET * PLAYER_SIZE * shorter_dimension).max(
+        bullet_spawn_clearance(collision_distance, shorter_dimension),
+    );
+    player + direction * offset
+}
+
+// the closest any new bullet, fired or from a wave, may start to the player's center
+fn bullet_spawn_clearance(collision_distance: f32, shorter_dimension: f32) -> f32 {
+    collision_distance + BULLET_SPAWN_CLEARANCE * PLAYER_SIZE * shorter_dimension
+}
+
+fn bullet_bundle(
+    bullet_data: &BulletRenderComponents,
+    trail_settings: &TrailSettings,
+    position: Vec3,
+    velocity: Vec3,
+) -> impl Bundle {
+    (
+        Bullet,
+        BulletFadeIn::default(),
+        TrailParticleSpawner {
+            timer: Timer::new(
+                Duration::from_secs_f32(trail_settings.interval),
+                TimerMode::Repeating,
+            ),
+        },
+        RibbonTrail::default(),
+        Mesh2d(bullet_data.mesh.clone()),
+        MeshMaterial2d(bullet_data.material.clone()),
+        Transform::from_translation(position.with_z(BULLET_LAYER)),
+        ScreenEdgeBouncer { velocity },
+    )
+}
+
+fn reset_wave_spawner(mut wave_spawner: ResMut<WaveSpawner>, game_mode: Res<SelectedGameMode>) {
+    let first_wave_delay = match game_mode.value {
+        GameMode::Survival => SURVIVAL_FIRST_WAVE_DELAY,
+        GameMode::Endless | GameMode::Timed | GameMode::ScoreAttack => WAVE_INTERVAL_START,
+    };
+    wave_spawner.timer = Timer::from_seconds(first_wave_delay, TimerMode::Once);
+}
+
+fn quick_restart_requested(quick_restart: Res<QuickRestart>) -> bool {
+    quick_restart.value
+}
+
+fn continue_quick_restart(
+    mut quick_restart: ResMut<QuickRestart>,
+    mut game_state: ResMut<NextState<AppState>>,
+) {
+    if quick_restart.value {
+        quick_restart.value = false;
+        game_state.set(AppState::InGame);
+    }
+}
+
+fn player_can_shoot(game_mode: Res<SelectedGameMode>) -> bool {
+    game_mode.value != GameMode::Survival
+}
+
+fn spawn_wave_warnings(
+    mut commands: Commands,
+    waves: Res<WavesEnabled>,
+    mut wave_spawner: ResMut<WaveSpawner>,
+    mut randomness: ResMut<RandomSource>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    display_properties: Res<DisplayProperties>,
+    intensity: Res<Intensity>,
+    time: Res<Time<Virtual>>,
+    game_mode: Res<SelectedGameMode>,
+) {
+    // survival mode has no other source of bullets, so its waves can't be turned off
+    if !waves.value && game_mode.value != GameMode::Survival {
+        return;
+    }
+
+    wave_spawner.timer.tick(time.delta());
+    if !wave_spawner.timer.is_finished() {
+        return;
+    }
+    wave_spawner.timer = Timer::from_seconds(
+        WAVE_INTERVAL_START.lerp(
+            WAVE_INTERVAL_MIN,
+            (intensity.survival_time / WAVE_INTERVAL_RAMP).min(1.0),
+        ),
+        TimerMode::Once,
+    );
+
+    let radius = PLAYER_SIZE * display_properties.shorter_dimension;
+    let w_margin = display_properties.half_w - radius * 1.5;
+    let h_margin = display_properties.half_h - radius * 1.5;
+    let mesh = meshes.add(Annulus::new(radius * 0.7, radius));
+    let bullet_count =
+        (1 + (intensity.survival_time / WAVE_BULLET_GROWTH) as usize).min(WAVE_MAX_BULLETS);
+
+    for _ in 0..bullet_count {
+        let rng = &mut randomness.0;
+        let along: f32 = rng.random_range(-1.0..1.0);
+        let position = match rng.random_range(0..4) {
+            0 => Vec3::new(along * w_margin, h_margin, 0.0),
+            1 => Vec3::new(along * w_margin, -h_margin, 0.0),
+            2 => Vec3::new(w_margin, along * h_margin, 0.0),
+            _ => Vec3::new(-w_margin, along * h_margin, 0.0),
+        };
+        // aim somewhere around the middle of the arena so the bullet always heads inwards
+        let target = Vec3::new(
+            rng.random_range(-0.5..0.5) * w_margin,
+            rng.random_range(-0.5..0.5) * h_margin,
+            0.0,
+        );
+
+        commands.spawn((
+            WaveWarning {
+                timer: Timer::from_seconds(WAVE_WARNING_DURATION, TimerMode::Once),
+                velocity: shot_direction(position, target),
+            },
+            DespawnOnEnter(AppState::Menu),
+            Mesh2d(mesh.clone()),
+            MeshMaterial2d(materials.add(ColorMaterial {
+                color: Color::srgba(1., 1., 1., 0.0),
+                alpha_mode: AlphaMode2d::Blend,
+                ..default()
+            })),
+            Transform::from_translation(position),
+        ));
+    }
+}
+
+fn handle_wave_warnings(
+    mut commands: Commands,
+    warnings: Query<(
+        Entity,
+        &Transform,
+        &mut WaveWarning,
+        &MeshMaterial2d<ColorMaterial>,
+    )>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    bullet_data: Res<BulletRenderComponents>,
+    trail_settings: Res<TrailSettings>,
+    time: Res<Time<Virtual>>,
+    player: Single<&Transform, With<Player>>,
+    display_properties: Res<DisplayProperties>,
+    (hitbox_scale, bullet_growth, assist_mode): (
+        Res<HitboxScale>,
+        Res<BulletGrowth>,
+        Res<AssistMode>,
+    ),
+) {
+    let clearance = bullet_spawn_clearance(
+        player_collision_distance(
+            &display_properties,
+            &hitbox_scale,
+            &bullet_growth,
+            &assist_mode,
+        ),
+        display_properties.shorter_dimension,
+    );
+    for (entity, transform, mut warning, material) in warnings {
+        warning.timer.tick(time.delta());
+
+        if warning.timer.is_finished() {
+            // the player may have walked onto the warning, a bullet born there would be an unfair death
+            let distance = transform
+                .translation
+                .truncate()
+                .distance(player.translation.truncate());
+            if distance >= clearance {
+                commands.spawn(bullet_bundle(
+                    &bullet_data,
+                    &trail_settings,
+                    transform.translation,
+                    warning.velocity,
+                ));
+            }
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // blinks faster as the spawn gets closer
+        let progress = warning.timer.fraction();
+        if let Some(mat) = materials.get_mut(material.id()) {
+            mat.color = mat
+                .color
+                .with_alpha((progress * progress * 40.0).sin().abs() * progress);
+        }
+    }
+}
+
+// falls back to shooting straight up when the aim sits on top of the player, instead of producing NaN
+fn shot_direction(player: Vec3, aim: Vec3) -> Vec3 {
+    let difference = aim - player;
+    Vec3::new(difference.x, difference.y, 0.0)
+        .try_normalize()
+        .unwrap_or(Vec3::Y)
+}
+
+fn handle_bounce_particles(
+    mut batch: ResMut<ParticleBatch>,
+    time: Res<Time<Virtual>>,
+    display_properties: Res<DisplayProperties>,
+) {
+    batch.bounce.retain_mut(|particle| {
+        particle.lifetime -= time.delta_secs();
+        if particle.lifetime < 0.0 {
+            return false;
+        }
+
+        particle.position += particle.velocity.truncate()
+            * ((PI / 2.0).lerp(0.0, particle.lifetime / TRAIL_PARTICLE_LIFETIME)).cos()
+            * COLLISION_PARTICLE_SPEED_NORMALIZED
+            * display_properties.shorter_dimension
+            * time.delta_secs();
+        true
+    });
+}
+
+fn handle_wall_ripples(mut batch: ResMut<ParticleBatch>, time: Res<Time<Virtual>>) {
+    batch.ripple.retain_mut(|ripple| {
+        ripple.lifetime -= time.delta_secs();
+        ripple.lifetime >= 0.0
+    });
+}
+
+fn detect_player_death(
+    mut commands: Commands,
+    mut bullets: Query<(&Transform, &mut ScreenEdgeBouncer), With<Bullet>>,
+    player: Single<(Entity, &Transform), (With<Player>, Without<Dead>)>,
+    display_properties: Res<DisplayProperties>,
+    practice_mode: Res<PracticeMode>,
+    hitbox_scale: Res<HitboxScale>,
+    bullet_growth: Res<BulletGrowth>,
+    mut died_writer: MessageWriter<PlayerDied>,
+    (assist_mode, mut recovery): (Res<AssistMode>, ResMut<Recovery>),
+    fixed_time: Res<Time<Fixed>>,
+    mut screen_flash: ResMut<ScreenFlash>,
+) {
+    if practice_mode.value {
+        return;
+    }
+    if recovery.invulnerable_remaining > 0.0 {
+        recovery.invulnerable_remaining -= fixed_time.delta_secs();
+        return;
+    }
+
+    let (player_entity, player_transform) = *player;
+    let collision_distance = player_collision_distance(
+        &display_properties,
+        &hitbox_scale,
+        &bullet_growth,
+        &assist_mode,
+    );
+
+    let player_position = player_transform.translation.truncate();
+    let hit = bullets.iter().any(|(bullet, _)| {
+        bullet.translation.truncate().distance(player_position) < collision_distance
+    });
+    if !hit {
+        return;
+    }
+
+    if recovery.revives > 0 {
+        recovery.revives -= 1;
+        recovery.invulnerable_remaining = REVIVE_RECOVERY_DURATION;
+        screen_flash.color = Color::WHITE;
+        screen_flash.intensity = SCREEN_FLASH_ON_DEATH;
+        // sends the crowd that caused the hit flying outward, so the revive isn't just a delayed death
+        let clear_radius = REVIVE_CLEAR_RADIUS * display_properties.shorter_dimension;
+        for (transform, mut bouncer) in &mut bullets {
+            let offset = transform.translation.truncate() - player_position;
+            if offset.length() < clear_radius {
+                let direction = offset.try_normalize().unwrap_or(Vec2::Y);
+                bouncer.velocity = direction.extend(0.0) * bouncer.velocity.length();
+            }
+        }
+        return;
+    }
+
+    // marking the player keeps any further fixed steps this frame from reporting the same death
+    commands.entity(player_entity).insert(Dead);
+    died_writer.write(PlayerDied);
+}
+
+// how close a bullet center can get to the player center before it counts as a hit
+fn player_collision_distance(
+    display_properties: &DisplayProperties,
+    hitbox_scale: &HitboxScale,
+    bullet_growth: &BulletGrowth,
+    assist_mode: &AssistMode,
+) -> f32 {
+    PLAYER_SIZE
+        * (bullet_growth.value + player_hitbox_scale(hitbox_scale, assist_mode))
+        * display_properties.shorter_dimension
+}
+
+fn player_hitbox_scale(hitbox_scale: &HitboxScale, assist_mode: &AssistMode) -> f32 {
+    if assist_mode.value {
+        hitbox_scale.value * ASSIST_HITBOX_FACTOR
+    } else {
+        hitbox_scale.value
+    }
+}
+
+fn end_run_on_death(
+    mut died_reader: MessageReader<PlayerDied>,
+    mut time: ResMut<Time<Virtual>>,
+    mut game_state: ResMut<NextState<AppState>>,
+    player: Single<&Transform, With<Player>>,
+    death_cinematic: Res<DeathCinematicEnabled>,
+    reduce_motion: Res<ReduceMotion>,
+    mut cinematic: ResMut<DeathCinematic>,
+) {
+    let deaths = died_reader.read().count();
+    if deaths == 0 {
+        return;
+    }
+    debug_assert_eq!(deaths, 1, "a single death should only be reported once");
+
+    time.pause();
+    if death_cinematic.value && !reduce_motion.value {
+        // the game over screen waits for the cinematic to finish
+        cinematic.active = true;
+        cinematic.elapsed = 0.0;
+        cinematic.focus = player.translation.truncate();
+        return;
+    }
+    game_state.set(AppState::GameOver);
+}
+
+// counts real time, the virtual clock is already paused on the hit
+fn advance_death_cinematic(
+    mut cinematic: ResMut<DeathCinematic>,
+    mut game_state: ResMut<NextState<AppState>>,
+    time: Res<Time<Real>>,
+) {
+    if !cinematic.active || cinematic.elapsed >= DEATH_CINEMATIC_DURATION {
+        return;
+    }
+
+    cinematic.elapsed += time.delta_secs();
+    if cinematic.elapsed >= DEATH_CINEMATIC_DURATION {
+        game_state.set(AppState::GameOver);
+    }
+}
+
+fn play_death_sound(
+    mut commands: Commands,
+    mut died_reader: MessageReader<PlayerDied>,
+    asset_server: Res<AssetServer>,
+) {
+    for _ in died_reader.read() {
+        commands.spawn((
+            AudioPlayer::new(asset_server.load("Random32.wav")),
+            PlaybackSettings::DESPAWN,
+        ));
+    }
+}
+
+fn rumble_on_death(mut died_reader: MessageReader<PlayerDied>, mut rumble: Rumble) {
+    for _ in died_reader.read() {
+        rumble.add(200, 0.9, 0.6);
+        rumble.add(400, 0.2, 0.5);
+    }
+}
+
+fn death_screen_feedback(
+    mut died_reader: MessageReader<PlayerDied>,
+    mut screenshake: ResMut<ScreenshakeIntensity>,
+    mut zoom_punch: ResMut<ZoomPunch>,
+    mut screen_flash: ResMut<ScreenFlash>,
+    screenshake_config: Res<ScreenshakeConfig>,
+) {
+    for _ in died_reader.read() {
+        screenshake.value += screenshake_config.kick(screenshake_config.on_death);
+        zoom_punch.value += ZOOM_PUNCH_ON_DEATH;
+        screen_flash.color = Color::WHITE;
+        screen_flash.intensity = SCREEN_FLASH_ON_DEATH;
+    }
+}
+
+// for two touching bullets, returns the velocities pushing them apart and their point of contact
+// runs for every pair of bullets, so no square root is taken until a collision is certain
+// pairs already moving apart are left alone, otherwise a pair that is still overlapping on the next step would
+// bounce again, compounding the elasticity and repeating the feedback every step
+fn resolve_bullet_pair(
+    (first_position, first): (Vec3, &ScreenEdgeBouncer),
+    (second_position, second): (Vec3, &ScreenEdgeBouncer),
+    collision_distance_squared: f32,
+    elasticity: f32,
+) -> Option<(Vec3, Vec3, Vec3)> {
+    let offset = first_position - second_position;
+    let distance_squared = offset.length_squared();
+    // bullets sitting right on top of each other have no meaningful direction to bounce in
+    if distance_squared > collision_distance_squared || distance_squared < 1.0 {
+        return None;
+    }
+    if (first.velocity - second.velocity).dot(offset) >= 0.0 {
+        return None;
+    }
+
+    let dir = offset / distance_squared.sqrt();
+    let first_velocity =
+        dir * (first.velocity.length() * elasticity).min(BULLET_MAX_SPEED_MULTIPLIER);
+    let second_velocity =
+        -dir * (second.velocity.length() * elasticity).min(BULLET_MAX_SPEED_MULTIPLIER);
+
+    Some((
+        first_velocity,
+        second_velocity,
+        (first_position + second_position) / 2.0,
+    ))
+}
+
+fn handle_bullet_collision(
+    mut commands: Commands,
+    mut bullets: Query<(&Transform, &mut ScreenEdgeBouncer), With<Bullet>>,
+    display_properties: Res<DisplayProperties>,
+    mut particle_batch: ResMut<ParticleBatch>,
+    mut randomness: ResMut<RandomSource>,
+    mut screenshake: ResMut<ScreenshakeIntensity>,
+    screenshake_config: Res<ScreenshakeConfig>,
+    mut zoom_punch: ResMut<ZoomPunch>,
+    asset_server: Res<AssetServer>,
+    particle_quality: Res<ParticleQualitySetting>,
+    elasticity: Res<BounceElasticity>,
+    bullet_growth: Res<BulletGrowth>,
+    bombs: Res<Bombs>,
+    (game_mode, mut score_attack): (Res<SelectedGameMode>, ResMut<ScoreAttack>),
+) {
+    // frozen bullets can't move apart, an overlapping pair would bounce again on every step
+    if bombs.freeze_remaining > 0.0 {
+        return;
+    }
+
+    let bounce_points = SCORE_ATTACK_POINTS_PER_BOUNCE * bullets.iter().len() as f32;
+
+    let collision_distance =
+        PLAYER_SIZE * 2.0 * bullet_growth.value * display_properties.shorter_dimension;
+    let collision_distance_squared = collision_distance * collision_distance;
+    let circle = Circle::new(1.0);
+
+    // yields nothing with fewer than two bullets, which is fine now that death detection lives elsewhere
+    let mut iter = bullets.iter_combinations_mut();
+    while let Some([(first, mut first_bouncer), (second, mut second_bouncer)]) = iter.fetch_next() {
+        // only read access until a collision is found, so untouched bullets aren't flagged as changed
+        let Some((first_velocity, second_velocity, average_position)) = resolve_bullet_pair(
+            (first.translation, &first_bouncer),
+            (second.translation, &second_bouncer),
+            collision_distance_squared,
+            elasticity.value,
+        ) else {
+            continue;
+        };
+        first_bouncer.velocity = first_velocity;
+        second_bouncer.velocity = second_velocity;
+        // the pair is separating from here on, so a bounce pays out once even while the bullets still overlap
+        if game_mode.value == GameMode::ScoreAttack {
+            score_attack.points += bounce_points;
+        }
+
+        screenshake.value += screenshake_config.kick(screenshake_config.on_bounce);
+        zoom_punch.value += ZOOM_PUNCH_ON_BOUNCE;
+        commands.spawn((
+            AudioPlayer::new(asset_server.load("Ball_Flick.wav")),
+            PlaybackSettings::DESPAWN,
+        ));
+
+        for _ in 0..particle_quality.value.collision_particle_count() {
+            let rng = &mut randomness.0;
+            let vel = circle.sample_boundary(rng);
+            particle_batch.bounce.push(BounceParticle {
+                position: average_position.truncate(),
+                lifetime: COLLISION_PARTICLE_LIFETIME,
+                velocity: Vec3::new(vel.x, vel.y, 0.0),
+            });
+        }
+    }
+}
+
+fn oscilate_bullet_colors(
+    time: Res<Time<Real>>,
+    bullet_data: Res<BulletRenderComponents>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    bloom: Res<BloomEnabled>,
+    reduce_motion: Res<ReduceMotion>,
+    bullet_color_setting: Res<BulletColorSetting>,
+) {
+    let hue = match bullet_color_setting.value {
+        // written once, then only again when the material is rebuilt or something it depends on changes
+        BulletColorMode::Static { hue } => {
+            if !bullet_data.is_changed()
+                && !bloom.is_changed()
+                && !bullet_color_setting.is_changed()
+            {
+                return;
+            }
+            hue
+        }
+        BulletColorMode::Oscillate if reduce_motion.value => REDUCED_MOTION_BULLET_HUE,
+        BulletColorMode::Oscillate => time.elapsed_secs() * BULLET_COLOR_OSCILATION_SPEED,
+    };
+    // the handle gets replaced whenever the bullet data is rebuilt, so a frame without the material is no reason to panic
+    let Some(mat) = materials.get_mut(bullet_data.material.id()) else {
+        return;
+    };
+    mat.color = bullet_color(hue, bloom.value);
+}
+
+// softens the pop of a fresh bullet for motion sensitive players, runs after the scale and color are set for the frame
+fn fade_in_bullets(
+    mut commands: Commands,
+    mut bullets: Query<(
+        Entity,
+        &mut BulletFadeIn,
+        &mut Transform,
+        &mut MeshMaterial2d<ColorMaterial>,
+    )>,
+    bullet_data: Res<BulletRenderComponents>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    reduce_motion: Res<ReduceMotion>,
+    time: Res<Time<Virtual>>,
+) {
+    let Some(color) = materials
+        .get(bullet_data.material.id())
+        .map(|mat| mat.color)
+    else {
+        return;
+    };
+
+    for (entity, mut fade, mut transform, mut material) in &mut bullets {
+        fade.age += time.delta_secs();
+        let progress = (fade.age / BULLET_FADE_IN_DURATION).min(1.0);
+        if !reduce_motion.value || progress >= 1.0 {
+            // dropping the component drops the last handle to its material too
+            material.0 = bullet_data.material.clone();
+            commands.entity(entity).remove::<BulletFadeIn>();
+            continue;
+        }
+
+        let eased = progress * (2.0 - progress);
+        transform.scale *= eased;
+        let handle = fade.material.get_or_insert_with(|| {
+            materials.add(ColorMaterial {
+                color,
+                alpha_mode: AlphaMode2d::Blend,
+                ..default()
+            })
+        });
+        if let Some(mat) = materials.get_mut(handle.id()) {
+            mat.color = color.with_alpha(eased);
+        }
+        material.0 = handle.clone();
+    }
+}
+
+fn bullet_speed_multiplier(
+    intensity: &Intensity,
+    assist_mode: &AssistMode,
+    sudden_death: &SuddenDeathConfig,
+) -> f32 {
+    let assist = if assist_mode.value {
+        ASSIST_BULLET_SPEED_FACTOR
+    } else {
+        1.0
+    };
+    (1.0 + intensity.value * INTENSITY_SPEED_BONUS)
+        * (1.0 + intensity.sudden_death * sudden_death.speed_bonus)
+        * assist
+}
+
+fn move_bouncers(
+    bullets: Query<(&mut Transform, &mut ScreenEdgeBouncer, Has<Bullet>)>,
+    fixed_time: Res<Time<Fixed>>,
+    display_properties: Res<DisplayProperties>,
+    elasticity: Res<BounceElasticity>,
+    intensity: Res<Intensity>,
+    bullet_growth: Res<BulletGrowth>,
+    bombs: Res<Bombs>,
+    (assist_mode, sudden_death): (Res<AssistMode>, Res<SuddenDeathConfig>),
+    mut particle_batch: ResMut<ParticleBatch>,
+    particle_quality: Res<ParticleQualitySetting>,
+) {
+    let bullet_speed = bullet_speed_multiplier(&intensity, &assist_mode, &sudden_death);
+    let mut ripple = |position: Vec2, normal: Vec2| {
+        particle_batch.ripple.push(WallRipple {
+            position,
+            normal,
+            lifetime: WALL_RIPPLE_LIFETIME,
+            dots: particle_quality.value.wall_ripple_dot_count(),
+        });
+    };
+    let bullets_frozen = bombs.freeze_remaining > 0.0;
+    for (mut trans, mut bouncer, is_bullet) in bullets {
+        if is_bullet && bullets_frozen {
+            continue;
+        }
+
+        let radius = if is_bullet { bullet_growth.value } else { 1.0 }
+            * PLAYER_SIZE
+            * display_properties.shorter_dimension;
+        // a bullet wider than the arena just bounces around its center instead of getting trapped
+        let w_margin = (display_properties.half_w - radius).max(0.0);
+        let h_margin = (display_properties.half_h - radius).max(0.0);
+        let speed = if is_bullet { bullet_speed } else { 1.0 };
+        trans.translation += bouncer.velocity
+            * speed
+            * BULLET_MOVEMENT_SPEED_NORMALIZED
+            * display_properties.shorter_dimension
+            * fixed_time.delta_secs();
+
+        // the ripple sits on the arena edge itself, not on the bullet center a radius away from it
+        let mut bounced = false;
+        if bouncer.velocity.x > 0.0 {
+            if trans.translation.x > w_margin {
+                bouncer.velocity.x = -bouncer.velocity.x;
+                bounced = true;
+                if is_bullet {
+                    ripple(
+                        Vec2::new(display_properties.half_w, trans.translation.y),
+                        Vec2::NEG_X,
+                    );
+                }
+            }
+        } else if trans.translation.x < -w_margin {
+            bouncer.velocity.x = -bouncer.velocity.x;
+            bounced = true;
+            if is_bullet {
+                ripple(
+                    Vec2::new(-display_properties.half_w, trans.translation.y),
+                    Vec2::X,
+                );
+            }
+        }
+
+        if bouncer.velocity.y > 0.0 {
+            if trans.translation.y > h_margin {
+                bouncer.velocity.y = -bouncer.velocity.y;
+                bounced = true;
+                if is_bullet {
+                    ripple(
+                        Vec2::new(trans.translation.x, display_properties.half_h),
+                        Vec2::NEG_Y,
+                    );
+                }
+            }
+        } else if trans.translation.y < -h_margin {
+            bouncer.velocity.y = -bouncer.velocity.y;
+            bounced = true;
+            if is_bullet {
+                ripple(
+                    Vec2::new(trans.translation.x, -display_properties.half_h),
+                    Vec2::Y,
+                );
+            }
+        }
+
+        if bounced && is_bullet {
+            bouncer.velocity =
+                (bouncer.velocity * elasticity.value).clamp_length_max(BULLET_MAX_SPEED_MULTIPLIER);
+        }
+    }
+}
+
+fn reset_bombs(mut bombs: ResMut<Bombs>) {
+    bombs.charges = BOMB_CHARGES;
+    bombs.freeze_remaining = 0.0;
+}
+
+// assist mode brings its own revive, it doesn't stack with the regular one
+fn reset_recovery(
+    mut recovery: ResMut<Recovery>,
+    revive: Res<ReviveEnabled>,
+    assist_mode: Res<AssistMode>,
+) {
+    recovery.revives = if revive.value || assist_mode.value {
+        1
+    } else {
+        0
+    };
+    recovery.invulnerable_remaining = if assist_mode.value {
+        ASSIST_GRACE_PERIOD
+    } else {
+        0.0
+    };
+}
+
+// blinks the player while it can't be hit, whether from a revive or the assist grace period
+fn flash_recovering_player(
+    recovery: Res<Recovery>,
+    mut player: Single<&mut Visibility, With<Player>>,
+    time: Res<Time<Virtual>>,
+) {
+    let visible = recovery.invulnerable_remaining <= 0.0
+        || (time.elapsed_secs() * RECOVERY_FLASH_RATE).fract() < 0.5;
+    player.set_if_neq(if visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+}
+
+// read straight from the devices, a press has to trigger exactly once no matter how many fixed steps a frame runs
+fn use_bomb(
+    mut bombs: ResMut<Bombs>,
+    bindings: Res<KeyBindings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    active_gamepad: Res<ActiveGamepad>,
+    time: Res<Time<Virtual>>,
+    (mut screenshake, screenshake_config): (ResMut<ScreenshakeIntensity>, Res<ScreenshakeConfig>),
+    mut screen_flash: ResMut<ScreenFlash>,
+    mut rumble: Rumble,
+) {
+    bombs.freeze_remaining = (bombs.freeze_remaining - time.delta_secs()).max(0.0);
+
+    let pressed = keyboard.just_pressed(bindings.bomb_key)
+        || mouse.just_pressed(bindings.bomb_mouse)
+        || active_gamepad
+            .value
+            .and_then(|entity| gamepads.get(entity).ok())
+            .is_some_and(|gamepad| gamepad.just_pressed(bindings.bomb_gamepad));
+    // a second bomb during a freeze would only waste a charge
+    if !pressed || bombs.charges == 0 || bombs.freeze_remaining > 0.0 {
+        return;
+    }
+
+    bombs.charges -= 1;
+    bombs.freeze_remaining = BOMB_FREEZE_DURATION;
+    screenshake.value += screenshake_config.kick(screenshake_config.on_bomb);
+    screen_flash.color = Color::srgb(0.6, 0.9, 1.0);
+    screen_flash.intensity = SCREEN_FLASH_ON_BOMB;
+    rumble.add(150, 0.6, 0.4);
+}
+
+fn update_bomb_display(bombs: Res<Bombs>, display: Query<&mut Text, With<BombDisplay>>) {
+    if !bombs.is_changed() {
+        return;
+    }
+
+    for mut text in display {
+        text.0 = format!("Bombs: {}", bombs.charges);
+    }
+}
+
+fn despawn_bullets(mut commands: Commands, bullets: Query<(Entity, &Bullet)>) {
+    for (entity_id, _) in bullets.iter() {
+        commands.entity(entity_id).despawn();
+    }
+}
+
+fn clear_particles(mut batch: ResMut<ParticleBatch>) {
+    batch.trail.clear();
+    batch.bounce.clear();
+    batch.ripple.clear();
+}
+
+// whichever way a run was left (pause menu, game over, or straight out of the opening seconds),
+// the menu always starts with the virtual clock running
+fn restore_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn spawn_player_aim(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    display_properties: Res<DisplayProperties>,
+    game_mode: Res<SelectedGameMode>,
+    reticle: Res<ReticleSettings>,
+) {
+    // nothing to aim without shooting, the aim systems simply skip without a reticle
+    if game_mode.value == GameMode::Survival {
+        return;
+    }
+
+    let radius = display_properties.shorter_dimension * PLAYER_SIZE * 0.5 * reticle.size;
+    let material = materials.add(ColorMaterial {
+        color: Color::WHITE.with_alpha(if reticle.visible { 1.0 } else { 0.0 }),
+        alpha_mode: AlphaMode2d::Blend,
+        ..default()
+    });
+    let transform = Transform::from_translation(Vec3::new(PLAYER_SIZE, PLAYER_SIZE, AIM_LAYER));
+    match reticle.shape {
+        ReticleShape::Dot => {
+            commands.spawn((
+                PlayerAim,
+                Mesh2d(meshes.add(Circle::new(radius))),
+                MeshMaterial2d(material),
+                transform,
+            ));
+        }
+        // the vertical bar is a child, so both move together with the aim entity
+        ReticleShape::Crosshair => {
+            let thickness = radius * RETICLE_CROSSHAIR_THICKNESS;
+            commands.spawn((
+                PlayerAim,
+                Mesh2d(meshes.add(Rectangle::new(radius * 3.0, thickness))),
+                MeshMaterial2d(material.clone()),
+                transform,
+                children![(
+                    Mesh2d(meshes.add(Rectangle::new(thickness, radius * 3.0))),
+                    MeshMaterial2d(material),
+                )],
+            ));
+        }
+    }
+}
+
+fn move_player_aim(
+    input: Res<InputState>,
+    mut player_aim: Single<&mut Transform, With<PlayerAim>>,
+    player: Single<&Transform, (With<Player>, Without<PlayerAim>)>,
+    fixed_time: Res<Time<Fixed>>,
+    display_properties: Res<DisplayProperties>,
+    aim_distance: Res<GamepadAimDistance>,
+    aim_assist: Res<AimAssist>,
+    bullets: Query<&Transform, (With<Bullet>, Without<Player>, Without<PlayerAim>)>,
+    aim_mode: Res<AimModeSetting>,
+    primary_device: Res<PrimaryControlDevice>,
+    movement: Res<MovementConfig>,
+    mut keyboard_aim_offset: Local<Option<Vec2>>,
+) {
+    match aim_mode.value {
+        AimMode::Relative => {
+            player_aim.translation += vec3(input.aim_motion.x, input.aim_motion.y, 0.);
+        }
+        // only snaps to the cursor when the mouse actually moved, so a gamepad can still take over
+        AimMode::Absolute => {
+            if input.aim_motion != Vec2::ZERO
+                && let Some(cursor) = input.cursor
+            {
+                player_aim.translation.x = cursor.x;
+                player_aim.translation.y = cursor.y;
+            }
+        }
+    }
+
+    // a keyboard player's reticle keeps its offset from the player, so walking around doesn't leave the aim behind
+    if primary_device.value == ControlDevice::Keyboard {
+        let offset = keyboard_aim_offset
+            .unwrap_or((player_aim.translation - player.translation).truncate())
+            + input.aim_keys.clamp_length_max(1.0)
+                * movement.keyboard_aim_speed
+                * display_properties.shorter_dimension
+                * fixed_time.delta_secs();
+        *keyboard_aim_offset = Some(offset);
+        player_aim.translation.x = player.translation.x + offset.x;
+        player_aim.translation.y = player.translation.y + offset.y;
+    } else {
+        *keyboard_aim_offset = None;
+    }
+
+    if let Some(mut movement_vector) = input.aim_stick {
+        if movement_vector.length() < GAMEPAD_AIM_DEADZONE {
+            return;
+        }
+
+        if aim_assist.value > 0.0 {
+            let nearest_bullet = bullets.iter().min_by(|a, b| {
+                a.translation
+                    .distance_squared(player.translation)
+                    .total_cmp(&b.translation.distance_squared(player.translation))
+            });
+            // only ever a partial pull on the stick direction, the lerp below keeps it from snapping
+            if let Some(threat) = nearest_bullet.and_then(|bullet| {
+                (bullet.translation - player.translation)
+                    .truncate()
+                    .try_normalize()
+            }) {
+                let stick_length = movement_vector.length();
+                let stick_direction = movement_vector / stick_length;
+                movement_vector = stick_direction
+                    .lerp(threat, aim_assist.value * AIM_ASSIST_MAX_BIAS)
+                    .normalize_or(stick_direction)
+                    * stick_length;
+            }
+        }
+
+        // past the deadzone the stick is remapped to 0..1, so a light push aims near and a full push aims far
+        let stick_length = movement_vector.length();
+        let reach = GAMEPAD_AIM_MIN_REACH.lerp(
+            1.0,
+            ((stick_length - GAMEPAD_AIM_DEADZONE) / (1.0 - GAMEPAD_AIM_DEADZONE)).clamp(0.0, 1.0),
+        );
+        movement_vector = movement_vector / stick_length * reach;
+
+        let lerp_delta = 10.0 * fixed_time.delta_secs();
+        player_aim.translation = player_aim.translation.lerp(
+            player.translation
+                + vec3(movement_vector.x, movement_vector.y, 0.)
+                    * aim_distance.value
+                    * display_properties.shorter_dimension,
+            if lerp_delta > 1.0 { 1.0 } else { lerp_delta },
+        );
+    }
+}
+
+fn clamp_player_aim(
+    mut player: Single<&mut Transform, With<PlayerAim>>,
+    display: Res<DisplayProperties>,
+) {
+    player.translation = clamped_aim(player.translation, &display);
+}
+
+fn clamped_aim(translation: Vec3, display: &DisplayProperties) -> Vec3 {
+    Vec3 {
+        x: translation.x.clamp(-display.half_w, display.half_w),
+        y: translation.y.clamp(-display.half_h, display.half_h),
+        z: AIM_LAYER,
+    }
+}
+
+// the fixed step picks mouse motion up only when it next runs, up to a whole step (about 16ms at the default
+// 64Hz) after the frame it arrived in, and a frame without any step leaves the reticle where it was
+// reading it here puts the reticle under the mouse on the very frame that is rendered
+// the gamepad and aim keys stay on the fixed step, they're already smoothed over time
+fn move_player_aim_per_frame(
+    mut motion: MessageReader<MouseMotion>,
+    window: Single<&Window>,
+    aim_mode: Res<AimModeSetting>,
+    frame_rate_aim: Res<FrameRateAim>,
+    mut player_aim: Single<&mut Transform, With<PlayerAim>>,
+    display: Res<DisplayProperties>,
+    follow: Res<CameraFollow>,
+) {
+    let mut aim_motion = Vec2::ZERO;
+    for mot in motion.read() {
+        aim_motion += Vec2 {
+            x: mot.delta.x,
+            y: -mot.delta.y,
+        };
+    }
+    if !frame_rate_aim.value || aim_motion == Vec2::ZERO {
+        return;
+    }
+
+    match aim_mode.value {
+        AimMode::Relative => {
+            player_aim.translation += vec3(aim_motion.x, aim_motion.y, 0.);
+        }
+        AimMode::Absolute => {
+            if let Some(cursor) = cursor_in_arena(&window, &follow) {
+                player_aim.translation.x = cursor.x;
+                player_aim.translation.y = cursor.y;
+            }
+        }
+    }
+    player_aim.translation = clamped_aim(player_aim.translation, &display);
+}
+
+// screenshake is left out, it settles too quickly to be worth chasing with the reticle
+fn cursor_in_arena(window: &Window, follow: &CameraFollow) -> Option<Vec2> {
+    window.cursor_position().map(|cursor| {
+        Vec2 {
+            x: cursor.x - window.width() / 2.0,
+            y: window.height() / 2.0 - cursor.y,
+        } * follow.zoom
+            + follow.offset
+    })
+}
+
+fn despawn_player_aim(mut commands: Commands, players: Query<(Entity, &PlayerAim)>) {
+    for (entity_id, _) in players.iter() {
+        commands.entity(entity_id).despawn();
+    }
+}
+
+fn despawn_player(mut commands: Commands, players: Query<(Entity, &Player)>) {
+    for (entity_id, _) in players.iter() {
+        commands.entity(entity_id).despawn();
+    }
+}
+
+fn spawn_player(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    display_properties: Res<DisplayProperties>,
+    practice_mode: Res<PracticeMode>,
+    player_trail: Res<PlayerTrailEnabled>,
+    trail_settings: Res<TrailSettings>,
+    hitbox_scale: Res<HitboxScale>,
+    assist_mode: Res<AssistMode>,
+) {
+    let radius = display_properties.shorter_dimension * PLAYER_SIZE;
+    // a ring with a separate core reads differently from the filled bullet circles, even in a crowd
+    let ring_mesh = meshes.add(Annulus::new(radius * (1.0 - PLAYER_RING_THICKNESS), radius));
+    let core_mesh = meshes.add(Circle::new(radius * PLAYER_CORE_SIZE));
+    let hitbox_mesh = meshes.add(Circle::new(
+        radius * player_hitbox_scale(&hitbox_scale, &assist_mode),
+    ));
+
+    let material = if practice_mode.value {
+        materials.add(ColorMaterial {
+            color: Color::srgba(1., 1., 1., PRACTICE_PLAYER_ALPHA),
+            alpha_mode: AlphaMode2d::Blend,
+            ..default()
+        })
+    } else {
+        materials.add(Color::srgb(1., 1., 1.))
+    };
+    let player = commands
+        .spawn((
+            Player {
+                bullet_timer: 2.0,
+                since_shot: None,
+            },
+            Mesh2d(ring_mesh),
+            MeshMaterial2d(material.clone()),
+            Transform::from_translation(Vec3::new(0., 0., PLAYER_LAYER)),
+            children![
+                (
+                    Mesh2d(core_mesh),
+                    MeshMaterial2d(material),
+                    Transform::from_translation(Vec3::new(0., 0., 0.1)),
+                ),
+                (
+                    HitboxIndicator,
+                    Mesh2d(hitbox_mesh),
+                    MeshMaterial2d(materials.add(HITBOX_INDICATOR_COLOR)),
+                    Transform::from_translation(Vec3::new(0., 0., 0.2)),
+                    Visibility::Hidden,
+                )
+            ],
+        ))
+        .id();
+
+    if player_trail.value {
+        commands.entity(player).insert(TrailParticleSpawner {
+            timer: Timer::new(
+                Duration::from_secs_f32(trail_settings.interval),
+                TimerMode::Repeating,
+            ),
+        });
+    }
+}
+
+// a slow breathing pulse while the player stands still, purely visual since every hit check uses the base size
+fn animate_idle_player(
+    mut player: Single<&mut Transform, With<Player>>,
+    input: Res<InputState>,
+    time: Res<Time<Virtual>>,
+    reduce_motion: Res<ReduceMotion>,
+) {
+    let target = if input.movement.length() < GAMEPAD_STICK_DEADZONE && !reduce_motion.value {
+        1.0 + (time.elapsed_secs() * PLAYER_IDLE_PULSE_SPEED).sin() * PLAYER_IDLE_PULSE_SCALE
+    } else {
+        1.0
+    };
+    // eases back in and out instead of snapping whenever the player starts or stops moving
+    let blend = (time.delta_secs() * 10.0).min(1.0);
+    player.scale = player.scale.lerp(Vec3::splat(target), blend);
+}
+
+fn read_device_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut motion: MessageReader<MouseMotion>,
+    gamepads: Query<&Gamepad>,
+    active_gamepad: Res<ActiveGamepad>,
+    mut primary_device: ResMut<PrimaryControlDevice>,
+    window: Single<&Window>,
+    mut input: ResMut<InputState>,
+    bindings: Res<KeyBindings>,
+    (frame_rate_aim, follow): (Res<FrameRateAim>, Res<CameraFollow>),
+) {
+    let mut movement_vector = Vec2::ZERO;
+
+    if keyboard_input.pressed(KeyCode::KeyW)
+        || keyboard_input.pressed(KeyCode::ArrowUp)
+        || keyboard_input.pressed(KeyCode::KeyZ)
+    {
+        movement_vector.y += 1.0;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+    if keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown) {
+        movement_vector.y -= 1.0;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+    if keyboard_input.pressed(KeyCode::KeyA)
+        || keyboard_input.pressed(KeyCode::ArrowLeft)
+        || keyboard_input.pressed(KeyCode::KeyQ)
+    {
+        movement_vector.x -= 1.0;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+    if keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight) {
+        movement_vector.x += 1.0;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+
+    if let Some(gamepad) = active_gamepad
+        .value
+        .and_then(|entity| gamepads.get(entity).ok())
+    {
+        let left_stick_x = gamepad.get(GamepadAxis::LeftStickX).unwrap();
+        if left_stick_x.abs() > GAMEPAD_STICK_DEADZONE {
+            movement_vector.x += left_stick_x;
+            primary_device.value = ControlDevice::Gamepad;
+        }
+        let left_stick_y = gamepad.get(GamepadAxis::LeftStickY).unwrap();
+        if left_stick_y.abs() > GAMEPAD_STICK_DEADZONE {
+            movement_vector.y += left_stick_y;
+            primary_device.value = ControlDevice::Gamepad;
+        }
+    }
+
+    let mut aim_keys = Vec2::ZERO;
+    for (key, direction) in [
+        (bindings.aim_up, Vec2::Y),
+        (bindings.aim_down, Vec2::NEG_Y),
+        (bindings.aim_left, Vec2::NEG_X),
+        (bindings.aim_right, Vec2::X),
+    ] {
+        if keyboard_input.pressed(key) {
+            aim_keys += direction;
+            primary_device.value = ControlDevice::Keyboard;
+        }
+    }
+
+    let mut focus = false;
+    if keyboard_input.pressed(bindings.focus_key) {
+        focus = true;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+    if let Some(gamepad) = active_gamepad
+        .value
+        .and_then(|entity| gamepads.get(entity).ok())
+        && gamepad.pressed(bindings.focus_gamepad)
+    {
+        focus = true;
+        primary_device.value = ControlDevice::Gamepad;
+    }
+
+    let mut aim_motion = Vec2::ZERO;
+    for mot in motion.read() {
+        aim_motion += Vec2 {
+            x: mot.delta.x,
+            y: -mot.delta.y,
+        };
+    }
+    // each reader sees every message once, the per frame reader has already moved the reticle by these
+    if frame_rate_aim.value {
+        aim_motion = Vec2::ZERO;
+    }
+
+    *input = InputState {
+        movement: movement_vector,
+        aim_motion,
+        aim_keys,
+        aim_stick: active_gamepad
+            .value
+            .and_then(|entity| gamepads.get(entity).ok())
+            .map(|gamepad| Vec2 {
+                x: gamepad.get(GamepadAxis::RightStickX).unwrap(),
+                y: gamepad.get(GamepadAxis::RightStickY).unwrap(),
+            }),
+        cursor: cursor_in_arena(&window, &follow),
+        focus,
+    };
+}
+
+// runs out into no input at all once the script is over
+#[cfg(test)]
+fn play_scripted_input(mut scripted: ResMut<ScriptedInput>, mut input: ResMut<InputState>) {
+    *input = scripted
+        .steps
+        .get(scripted.next)
+        .copied()
+        .unwrap_or_default();
+    scripted.next += 1;
+}
+
+fn move_player(
+    input: Res<InputState>,
+    mut player: Single<&mut Transform, With<Player>>,
+    fixed_time: Res<Time<Fixed>>,
+    display_properties: Res<DisplayProperties>,
+    movement: Res<MovementConfig>,
+    intensity: Res<Intensity>,
+) {
+    let focus = if input.focus {
+        FOCUS_SPEED_MULTIPLIER
+    } else {
+        1.0
+    };
+    player.translation += vec3(input.movement.x, input.movement.y, 0.).clamp_length_max(1.0)
+        * fixed_time.delta_secs()
+        * movement.speed_at(&intensity)
+        * focus
+        * display_properties.shorter_dimension;
+}
+
+fn show_hitbox_on_focus(
+    input: Res<InputState>,
+    mut indicator: Single<&mut Visibility, With<HitboxIndicator>>,
+) {
+    let visibility = if input.focus {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    indicator.set_if_neq(visibility);
+}
+
+fn clamp_player(mut player: Single<&mut Transform, With<Player>>, display: Res<DisplayProperties>) {
+    let ps = PLAYER_SIZE * display.shorter_dimension;
+    // never negative, an inverted range would make clamp panic
+    let w_margin = (display.half_w - ps).max(0.0);
+    let h_margin = (display.half_h - ps).max(0.0);
+    player.translation = Vec3 {
+        x: player.translation.x.clamp(-w_margin, w_margin),
+        y: player.translation.y.clamp(-h_margin, h_margin),
+        z: PLAYER_LAYER,
+    }
+}
+
+fn reset_ghost_recorder(mut recorder: ResMut<GhostRecorder>) {
+    recorder.samples.clear();
+}
+
+fn record_ghost(
+    mut recorder: ResMut<GhostRecorder>,
+    player: Single<&Transform, With<Player>>,
+    display_properties: Res<DisplayProperties>,
+) {
+    recorder
+        .samples
+        .push(player.translation.truncate() / display_properties.shorter_dimension);
+}
+
+fn spawn_ghost_player(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    display_properties: Res<DisplayProperties>,
+    best_ghost: Res<BestGhost>,
+    game_mode: Res<SelectedGameMode>,
+) {
+    if best_ghost.samples.is_empty() || game_mode.value != GameMode::Endless {
+        return;
+    }
+
+    let radius = display_properties.shorter_dimension * PLAYER_SIZE;
+    commands.spawn((
+        GhostPlayer { index: 0 },
+        DespawnOnEnter(AppState::Menu),
+        Mesh2d(meshes.add(Annulus::new(radius * (1.0 - PLAYER_RING_THICKNESS), radius))),
+        MeshMaterial2d(materials.add(ColorMaterial {
+            color: Color::srgba(1., 1., 1., GHOST_ALPHA),
+            alpha_mode: AlphaMode2d::Blend,
+            ..default()
+        })),
+        Transform::from_translation(Vec3::new(0., 0., GHOST_LAYER)),
+    ));
+}
+
+fn move_ghost_player(
+    mut commands: Commands,
+    ghosts: Query<(Entity, &mut Transform, &mut GhostPlayer)>,
+    best_ghost: Res<BestGhost>,
+    display_properties: Res<DisplayProperties>,
+) {
+    for (entity, mut transform, mut ghost) in ghosts {
+        let Some(sample) = best_ghost.samples.get(ghost.index) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let position = *sample * display_properties.shorter_dimension;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        ghost.index += 1;
+    }
+}
+
+// every finished run counts, whatever ended it, only runs abandoned through the pause menu are left out
+fn record_session_run(
+    mut stats: ResMut<SessionStats>,
+    score: Res<Score>,
+    practice_mode: Res<PracticeMode>,
+) {
+    if practice_mode.value {
+        return;
+    }
+
+    stats.runs_played += 1;
+    stats.best_this_session = stats.best_this_session.max(score.value);
+    if score.value >= SESSION_STREAK_SURVIVAL {
+        stats.current_streak += 1;
+        stats.longest_streak = stats.longest_streak.max(stats.current_streak);
+    } else {
+        stats.current_streak = 0;
+    }
+}
+
+fn session_stats_text(stats: &SessionStats, precision: TimerPrecision) -> String {
+    format!(
+        "Runs: {}  Best: {}  Streak: {} (longest {})",
+        stats.runs_played,
+        convert_time_to_text(stats.best_this_session, precision),
+        stats.current_streak,
+        stats.longest_streak
+    )
+}
+
+// only endless runs are comparable with each other, and practice or assisted runs don't count
+fn save_ghost_if_best(
+    recorder: Res<GhostRecorder>,
+    mut best_ghost: ResMut<BestGhost>,
+    score: Res<Score>,
+    game_mode: Res<SelectedGameMode>,
+    practice_mode: Res<PracticeMode>,
+    assist_mode: Res<AssistMode>,
+) {
+    if game_mode.value != GameMode::Endless
+        || practice_mode.value
+        || assist_mode.value
+        || score.value <= best_ghost.duration
+    {
+        return;
+    }
+
+    best_ghost.duration = score.value;
+    best_ghost.samples = recorder.samples.clone();
+
+    if let Err(e) = std::fs::write(GHOST_FILE_PATH, serialize_ghost(&best_ghost)) {
+        error!("failed to save the ghost: {}", e);
+    }
+}
+
+fn load_config() -> GameConfig {
+    let Ok(text) = std::fs::read_to_string(CONFIG_FILE_PATH) else {
+        return GameConfig::default();
+    };
+
+    match ron::from_str::<GameConfig>(&text) {
+        Ok(config) => {
+            // the fields may mean something else in another version, so none of them are trusted
+            if config.version != CONFIG_VERSION {
+                warn!(
+                    "config version {} differs from {}, using defaults",
+                    config.version, CONFIG_VERSION
+                );
+                return GameConfig::default();
+            }
+            config
+        }
+        Err(e) => {
+            warn!("failed to read the config, using defaults: {}", e);
+            GameConfig::default()
+        }
+    }
+}
+
+fn save_config(config: &GameConfig) {
+    let text = match ron::ser::to_string_pretty(config, ron::ser::PrettyConfig::default()) {
+        Ok(text) => text,
+        Err(e) => {
+            error!("failed to serialize the config: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = std::fs::write(CONFIG_FILE_PATH, text) {
+        error!("failed to save the config: {}", e);
+    }
+}
+
+// the resources count as changed when they're first inserted, which isn't worth a write
+fn mark_config_dirty(settings: GameSettings, mut pending_saves: ResMut<PendingSaves>) {
+    if settings.any_changed() && !settings.game_mode.is_added() {
+        pending_saves.config = true;
+    }
+}
+
+fn flush_pending_saves(settings: GameSettings, mut pending_saves: ResMut<PendingSaves>) {
+    if pending_saves.config {
+        save_config(&settings.to_config());
+        pending_saves.config = false;
+    }
+}
+
+// the quit button, and everything else that ends up sending AppExit
+// anything already written by the close request hook is no longer pending, so nothing is saved twice
+fn flush_pending_saves_on_exit(
+    mut exit_reader: MessageReader<AppExit>,
+    settings: GameSettings,
+    pending_saves: ResMut<PendingSaves>,
+) {
+    if exit_reader.read().last().is_some() {
+        flush_pending_saves(settings, pending_saves);
+    }
+}
+
+// the os close button and alt-F4 take the window down before AppExit is even sent, so save right away
+fn flush_pending_saves_on_close_request(
+    mut close_reader: MessageReader<WindowCloseRequested>,
+    settings: GameSettings,
+    pending_saves: ResMut<PendingSaves>,
+) {
+    if close_reader.read().last().is_some() {
+        flush_pending_saves(settings, pending_saves);
+    }
+}
+
+// plain text: the run duration on the first line, followed by one "x y" pair per line
+fn serialize_ghost(ghost: &BestGhost) -> String {
+    let mut text = ghost.duration.to_string();
+    for sample in &ghost.samples {
+        text.push_str(&format!("\n{} {}", sample.x, sample.y));
+    }
+    text
+}
+
+fn deserialize_ghost(text: &str) -> Option<BestGhost> {
+    let mut lines = text.lines();
+    let duration: f32 = lines.next()?.trim().parse().ok()?;
+
+    let mut samples = Vec::new();
+    for line in lines {
+        let mut values = line.split_whitespace();
+        let x: f32 = values.next()?.parse().ok()?;
+        let y: f32 = values.next()?.parse().ok()?;
+        samples.push(Vec2::new(x, y));
+    }
+
+    Some(BestGhost { duration, samples })
+}
+
+fn load_best_ghost() -> BestGhost {
+    std::fs::read_to_string(GHOST_FILE_PATH)
+        .ok()
+        .and_then(|text| deserialize_ghost(&text))
+        .unwrap_or(BestGhost {
+            duration: 0.0,
+            samples: Vec::new(),
+        })
+}
+
+fn handle_game_pausing(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    mut primary_device: ResMut<PrimaryControlDevice>,
+    mut time: ResMut<Time<Virtual>>,
+    mut game_state: ResMut<NextState<AppState>>,
+    state: Res<State<AppState>>,
+    mut buffer: ResMut<PauseInputBuffer>,
+    real_time: Res<Time<Real>>,
+    cinematic: Res<DeathCinematic>,
+    bindings: Res<KeyBindings>,
+) {
+    let mut take_action: bool = false;
+    buffer.cooldown -= real_time.delta_secs();
+    if keyboard_input.just_pressed(KeyCode::Escape)
+        || keyboard_input.just_pressed(KeyCode::Backspace)
+    {
+        take_action = true;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+
+    for (_entity, gamepad) in &gamepads {
+        if take_action {
+            break;
+        }
+
+        let just_pressed = gamepad.get_just_pressed().into_iter();
+        for button in just_pressed {
+            if bindings.is_pause_button(*button) {
+                take_action = true;
+                primary_device.value = ControlDevice::Gamepad;
+                break;
+            }
+        }
+    }
+
+    // presses landing while a state change is still queued are kept for the next frame instead of being lost,
+    // and the cooldown stops a single press from being read twice around the transition
+    if take_action && buffer.cooldown <= 0.0 {
+        buffer.pending = true;
+    }
+    if !buffer.pending || !matches!(*game_state, NextState::Unchanged) {
+        return;
+    }
+    buffer.pending = false;
+
+    // the run is already over during the death cinematic, unpausing from there would bring it back
+    if *state.get() == AppState::InGame && !cinematic.active {
+        time.pause();
+        game_state.set(AppState::Paused);
+    } else if *state.get() == AppState::Paused {
+        time.unpause();
+        game_state.set(AppState::InGame);
+    } else if *state.get() == AppState::Settings {
+        game_state.set(AppState::Menu);
+    } else if *state.get() == AppState::Data {
+        game_state.set(AppState::Settings);
+    } else {
+        return;
+    }
+    buffer.cooldown = PAUSE_TOGGLE_COOLDOWN;
+}
+
+fn handle_game_over_continue(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    mut primary_device: ResMut<PrimaryControlDevice>,
+    mut game_state: ResMut<NextState<AppState>>,
+    mut time: ResMut<Time<Virtual>>,
+    mouse_press: Res<ButtonInput<MouseButton>>,
+    bindings: Res<KeyBindings>,
+) {
+    let mut take_action: bool = false;
+    if keyboard_input.just_pressed(KeyCode::Escape)
+        || keyboard_input.just_pressed(KeyCode::Backspace)
+        || keyboard_input.just_pressed(KeyCode::Space)
+        || keyboard_input.just_pressed(KeyCode::Enter)
+    {
+        take_action = true;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+
+    // gamepads are queried fresh every frame, so a controller that was idle at the moment of death
+    // (or connected afterwards) is picked up as soon as it reports a button press
+    for (_entity, gamepad) in &gamepads {
+        if take_action {
+            break;
+        }
+
+        let just_pressed = gamepad.get_just_pressed().into_iter();
+        for button in just_pressed {
+            if bindings.is_pause_button(*button)
+                || *button == GamepadButton::Select
+                || *button == GamepadButton::Start
+                || *button == GamepadButton::South
+                || *button == GamepadButton::East
+                || *button == GamepadButton::North
+                || *button == GamepadButton::West
+            {
+                take_action = true;
+                primary_device.value = ControlDevice::Gamepad;
+                break;
+            }
+        }
+    }
+
+    if mouse_press.just_pressed(MouseButton::Left) || mouse_press.just_pressed(MouseButton::Right) {
+        take_action = true;
+        primary_device.value = ControlDevice::Mouse;
+    }
+
+    if take_action {
+        game_state.set(AppState::Menu);
+        time.unpause();
+    }
+}
+
+// drops a disconnected gamepad and hands control to the next one that gives any input
+fn select_active_gamepad(
+    mut active_gamepad: ResMut<ActiveGamepad>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    mut primary_device: ResMut<PrimaryControlDevice>,
+    (state, mut game_state, mut time): (
+        Res<State<AppState>>,
+        ResMut<NextState<AppState>>,
+        ResMut<Time<Virtual>>,
+    ),
+    pause_on_disconnect: Res<PauseOnDisconnect>,
+    mut disconnected: ResMut<ControllerDisconnected>,
+    cinematic: Res<DeathCinematic>,
+) {
+    if active_gamepad
+        .value
+        .is_some_and(|entity| !gamepads.contains(entity))
+    {
+        active_gamepad.value = None;
+        // a game paused from the controller has to stay resumable and navigable without it
+        if primary_device.value == ControlDevice::Gamepad {
+            primary_device.value = ControlDevice::Keyboard;
+
+            // same guard as the pause button, the run is already over during the death cinematic
+            if pause_on_disconnect.value && *state.get() == AppState::InGame && !cinematic.active {
+                time.pause();
+                game_state.set(AppState::Paused);
+                disconnected.value = true;
+            }
+        }
+    }
+
+    if active_gamepad.value.is_some() {
+        return;
+    }
+
+    for (entity, gamepad) in &gamepads {
+        if gamepad.get_just_pressed().next().is_some()
+            || gamepad.left_stick().length() > GAMEPAD_STICK_DEADZONE
+            || gamepad.right_stick().length() > GAMEPAD_AIM_DEADZONE
+        {
+            active_gamepad.value = Some(entity);
+            return;
+        }
+    }
+}
+
+fn check_for_mouse_input(
+    mut motion: MessageReader<MouseMotion>,
+    mut primary_device: ResMut<PrimaryControlDevice>,
+) {
+    // each message already carries the movement since the last one, so compare its length directly
+    for ev in motion.read() {
+        if ev.delta.length() > MOUSE_DEADZONE {
+            primary_device.value = ControlDevice::Mouse;
+        }
+    }
+}
+
+// This system handles changing all buttons color based on mouse interaction
+// the current selection is kept until the cursor actually hovers a different button
+fn button_react_to_mouse_system(
+    mut commands: Commands,
+    interaction_query: Query<
+        (Entity, &Interaction, Has<SelectedOption>),
+        (With<Button>, Without<Disabled>),
+    >,
+    selected_options: Query<Entity, With<SelectedOption>>,
+    primary_device: Res<PrimaryControlDevice>,
+) {
+    if primary_device.value != ControlDevice::Mouse {
+        return;
+    }
+
+    for (entity, interaction, selected) in &interaction_query {
+        if *interaction == Interaction::None || selected {
+            continue;
+        }
+
+        for previous in &selected_options {
+            commands.entity(previous).remove::<SelectedOption>();
+        }
+        commands.entity(entity).insert(SelectedOption);
+        break;
+    }
+}
+
+// makes sure a menu always has something highlighted, whichever device opened it
+fn ensure_menu_selection(
+    mut commands: Commands,
+    button_holder_query: Query<&Children, With<ButtonsHolder>>,
+    buttons: Query<Entity, (With<Button>, Without<Disabled>)>,
+    selected_options: Query<Entity, With<SelectedOption>>,
+) {
+    if !selected_options.is_empty() {
+        return;
+    }
+
+    for children in &button_holder_query {
+        if let Some(first) =
+            selectable_entities(children, |entity| buttons.contains(entity)).first()
+        {
+            commands.entity(*first).insert(SelectedOption);
+            return;
+        }
+    }
+}
+
+// menus can mix buttons with labels or disabled entries, only these are reachable by navigation
+fn selectable_entities(children: &Children, is_selectable: impl Fn(Entity) -> bool) -> Vec<Entity> {
+    children
+        .into_iter()
+        .copied()
+        .filter(|child| is_selectable(*child))
+        .collect()
+}
+
+// index reached by moving `step` entries away from `current`, wrapping around both ends
+fn step_selection(current: usize, step: isize, count: usize) -> usize {
+    (current as isize + step).rem_euclid(count as isize) as usize
+}
+
+fn button_react_to_keyboard_or_gamepad_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    mut commands: Commands,
+    interaction_query: Query<(Entity, Has<SelectedOption>), (With<Button>, Without<Disabled>)>,
+    button_holder_query: Query<(Entity, &Children), With<ButtonsHolder>>,
+    mut primary_device: ResMut<PrimaryControlDevice>,
+    mut repeat: ResMut<MenuRepeatTimer>,
+    time: Res<Time<Real>>,
+) {
+    let mut movement_vector = Vec2::ZERO;
+    let mut confirm_command: bool = false;
+    let mut held_direction: f32 = 0.0;
+
+    if keyboard_input.just_pressed(KeyCode::KeyW)
+        || keyboard_input.just_pressed(KeyCode::ArrowUp)
+        || keyboard_input.just_pressed(KeyCode::KeyZ)
+    {
+        movement_vector.y += 1.0;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyS) || keyboard_input.just_pressed(KeyCode::ArrowDown)
+    {
+        movement_vector.y -= 1.0;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Enter) || keyboard_input.just_pressed(KeyCode::Space) {
+        confirm_command = true;
+        primary_device.value = ControlDevice::Keyboard;
+    }
+
+    for (_entity, gamepad) in &gamepads {
+        let just_pressed = gamepad.get_just_pressed().into_iter();
+        for button in just_pressed {
+            if *button == GamepadButton::South || *button == GamepadButton::East {
+                confirm_command = true;
+                primary_device.value = ControlDevice::Gamepad;
+            }
+
+            if *button == GamepadButton::DPadUp {
+                movement_vector.y += 1.0;
+                primary_device.value = ControlDevice::Gamepad;
+            }
+            if *button == GamepadButton::DPadDown {
+                movement_vector.y -= 1.0;
+                primary_device.value = ControlDevice::Gamepad;
+            }
+        }
+
+        if gamepad.pressed(GamepadButton::DPadUp) {
+            held_direction += 1.0;
+        }
+        if gamepad.pressed(GamepadButton::DPadDown) {
+            held_direction -= 1.0;
+        }
+    }
+
+    if keyboard_input.pressed(KeyCode::KeyW)
+        || keyboard_input.pressed(KeyCode::ArrowUp)
+        || keyboard_input.pressed(KeyCode::KeyZ)
+    {
+        held_direction += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown) {
+        held_direction -= 1.0;
+    }
+
+    // the first step comes from just_pressed above, holding keeps scrolling after a delay
+    held_direction = held_direction.clamp(-1.0, 1.0);
+    if held_direction != repeat.direction || movement_vector.y != 0.0 {
+        repeat.direction = held_direction;
+        repeat.timer = Timer::from_seconds(MENU_REPEAT_INITIAL_DELAY, TimerMode::Once);
+    } else if held_direction != 0.0 {
+        repeat.timer.tick(time.delta());
+        if repeat.timer.is_finished() {
+            movement_vector.y = held_direction;
+            repeat.timer = Timer::from_seconds(MENU_REPEAT_INTERVAL, TimerMode::Once);
+        }
+    }
+
+    let step = if movement_vector.y > GAMEPAD_STICK_DEADZONE {
+        -1
+    } else if movement_vector.y < -GAMEPAD_STICK_DEADZONE {
+        1
+    } else {
+        0
+    };
+
+    if step != 0 {
+        for (_, children) in button_holder_query {
+            let buttons =
+                selectable_entities(children, |entity| interaction_query.contains(entity));
+            if buttons.is_empty() {
+                continue;
+            }
+
+            let selected_index = buttons.iter().position(|entity| {
+                interaction_query
+                    .get(*entity)
+                    .is_ok_and(|(_, selected)| selected)
+            });
+            if let Some(index) = selected_index {
+                commands.entity(buttons[index]).remove::<SelectedOption>();
+            }
+            // with nothing selected yet, moving down lands on the first entry and moving up on the last
+            let next = match selected_index {
+                Some(index) => step_selection(index, step, buttons.len()),
+                None if step > 0 => 0,
+                None => buttons.len() - 1,
+            };
+            commands.entity(buttons[next]).insert(SelectedOption);
+        }
+    }
+
+    if confirm_command {
+        for (entity, selected) in &interaction_query {
+            if !selected {
+                continue;
+            }
+
+            commands.entity(entity).insert(Interaction::Pressed);
+        }
+    }
+}
+fn button_handle_display(
+    mut button_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            Option<&SelectedOption>,
+            Has<Disabled>,
+        ),
+        With<Button>,
+    >,
+    brightness: Res<Brightness>,
+) {
+    for (interaction, mut background_color, selected, disabled) in &mut button_query {
+        let color = if disabled {
+            DISABLED_BUTTON
+        } else {
+            match (*interaction, selected) {
+                (Interaction::Pressed, Some(_)) => PRESSED_BUTTON,
+                (_, Some(_)) => HOVERED_BUTTON,
+                (_, _) => IDLE_BUTTON,
+            }
+        };
+        *background_color = brighten(color, brightness.value).into();
+    }
+}
+
+fn brighten(color: Color, brightness: f32) -> Color {
+    let mut hsva = Hsva::from(color);
+    hsva.value = (hsva.value * brightness).min(1.0);
+    hsva.into()
+}
+
+// text is spawned in the plain TEXT_COLOR everywhere and brightened here, both when it first shows up
+// and again for everything already on screen when the setting changes
+fn apply_text_brightness(
+    brightness: Res<Brightness>,
+    mut applied: Local<Option<f32>>,
+    texts: Query<&mut TextColor>,
+) {
+    let previous = applied.unwrap_or(brightness.value);
+    let stale = brighten(TEXT_COLOR, previous);
+    let target = brighten(TEXT_COLOR, brightness.value);
+    for mut color in texts {
+        let fresh = color.is_added() && color.0 == TEXT_COLOR;
+        if fresh || (previous != brightness.value && color.0 == stale) {
+            color.0 = target;
+        }
+    }
+    *applied = Some(brightness.value);
+}
+
+fn menu_action(
+    interaction_query: Query<
+        (&Interaction, &MenuButtonAction),
+        (Changed<Interaction>, With<Button>, Without<Disabled>),
+    >,
+    mut app_exit_writer: MessageWriter<AppExit>,
+    mut game_state: ResMut<NextState<AppState>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut settings: GameSettings,
+    (mut seed_entry, current_seed): (ResMut<SeedEntry>, Res<CurrentSeed>),
+    mut quick_restart: ResMut<QuickRestart>,
+    (mut commands, mut data_confirm, mut best_ghost, mut pending_saves): (
+        Commands,
+        ResMut<DataConfirm>,
+        ResMut<BestGhost>,
+        ResMut<PendingSaves>,
+    ),
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            let data_action = match menu_button_action {
+                MenuButtonAction::ResetBestRun => Some(DataAction::ResetBestRun),
+                MenuButtonAction::ResetSettings => Some(DataAction::ResetSettings),
+                _ => None,
+            };
+            let confirmed = data_action.is_some() && data_confirm.armed == data_action;
+            let armed = if confirmed { None } else { data_action };
+            if data_confirm.armed != armed {
+                data_confirm.armed = armed;
+            }
+
+            match menu_button_action {
+                MenuButtonAction::Quit => {
+                    app_exit_writer.write(AppExit::Success);
+                }
+                MenuButtonAction::Play => {
+                    game_state.set(AppState::InGame);
+                }
+                MenuButtonAction::Resume => {
+                    game_state.set(AppState::InGame);
+                    time.unpause();
+                }
+                MenuButtonAction::Restart => {
+                    quick_restart.value = true;
+                    game_state.set(AppState::Menu);
+                    time.unpause();
+                }
+                MenuButtonAction::ToMenu => {
+                    game_state.set(AppState::Menu);
+                    time.unpause();
+                }
+                MenuButtonAction::ToSettings => {
+                    game_state.set(AppState::Settings);
+                }
+                MenuButtonAction::ToData => {
+                    game_state.set(AppState::Data);
+                }
+                MenuButtonAction::ResetBestRun => {
+                    if !confirmed {
+                        continue;
+                    }
+                    if let Err(e) = std::fs::remove_file(GHOST_FILE_PATH)
+                        && e.kind() != std::io::ErrorKind::NotFound
+                    {
+                        error!("failed to delete the ghost: {}", e);
+                    }
+                    best_ghost.duration = 0.0;
+                    best_ghost.samples.clear();
+                }
+                // the font stays loaded from the old path until the next launch
+                MenuButtonAction::ResetSettings => {
+                    if !confirmed {
+                        continue;
+                    }
+                    save_config(&GameConfig::default());
+                    pending_saves.config = false;
+                    commands
+                        .queue(|world: &mut World| GameConfig::default().insert_resources(world));
+                }
+                // pressing the seed button replays the last run, pressing it again goes back to random
+                MenuButtonAction::CycleSeed => {
+                    seed_entry.value = match seed_entry.value {
+                        Some(_) => None,
+                        None => Some(current_seed.value),
+                    };
+                }
+                MenuButtonAction::CycleGameMode => {
+                    settings.game_mode.value = match settings.game_mode.value {
+                        GameMode::Endless => GameMode::Timed,
+                        GameMode::Timed => GameMode::Survival,
+                        GameMode::Survival => GameMode::ScoreAttack,
+                        GameMode::ScoreAttack => GameMode::Endless,
+                    };
+                }
+                MenuButtonAction::CycleParticleQuality => {
+                    settings.particle_quality.value = match settings.particle_quality.value {
+                        ParticleQuality::Low => ParticleQuality::Medium,
+                        ParticleQuality::Medium => ParticleQuality::High,
+                        ParticleQuality::High => ParticleQuality::Low,
+                    };
+                }
+                MenuButtonAction::ToggleReduceMotion => {
+                    settings.reduce_motion.value = !settings.reduce_motion.value;
+                }
+                MenuButtonAction::ToggleBloom => {
+                    settings.bloom.value = !settings.bloom.value;
+                }
+                MenuButtonAction::ToggleBackgroundTint => {
+                    settings.background_tint.value = !settings.background_tint.value;
+                }
+                MenuButtonAction::ToggleDeathCinematic => {
+                    settings.death_cinematic.value = !settings.death_cinematic.value;
+                }
+                MenuButtonAction::TogglePauseOnDisconnect => {
+                    settings.pause_on_disconnect.value = !settings.pause_on_disconnect.value;
+                }
+                MenuButtonAction::TogglePractice => {
+                    settings.practice.value = !settings.practice.value;
+                }
+                MenuButtonAction::ToggleAssist => {
+                    settings.assist.value = !settings.assist.value;
+                }
+                MenuButtonAction::ToggleRevive => {
+                    settings.revive.value = !settings.revive.value;
+                }
+                MenuButtonAction::CycleElasticity => {
+                    settings.elasticity.value =
+                        if settings.elasticity.value >= BOUNCE_ELASTICITY_HIGH {
+                            1.0
+                        } else if settings.elasticity.value >= BOUNCE_ELASTICITY_LOW {
+                            BOUNCE_ELASTICITY_HIGH
+                        } else {
+                            BOUNCE_ELASTICITY_LOW
+                        };
+                }
+                MenuButtonAction::ToggleWaves => {
+                    settings.waves.value = !settings.waves.value;
+                }
+                MenuButtonAction::ToggleHeatmap => {
+                    settings.heatmap.value = !settings.heatmap.value;
+                }
+                MenuButtonAction::CycleRumbleStrength => {
+                    settings.rumble_strength.value = RUMBLE_STRENGTH_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.rumble_strength.value + f32::EPSILON)
+                        .unwrap_or(RUMBLE_STRENGTH_STEPS[0]);
+                }
+                MenuButtonAction::CycleScreenshakeScale => {
+                    settings.screenshake.scale = SCREENSHAKE_SCALE_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.screenshake.scale + f32::EPSILON)
+                        .unwrap_or(SCREENSHAKE_SCALE_STEPS[0]);
+                }
+                MenuButtonAction::CycleCameraFollow => {
+                    settings.camera_follow.value = CAMERA_FOLLOW_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.camera_follow.value + f32::EPSILON)
+                        .unwrap_or(CAMERA_FOLLOW_STEPS[0]);
+                }
+                MenuButtonAction::CyclePlayerSpeed => {
+                    settings.movement.speed_scale = PLAYER_SPEED_SCALE_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.movement.speed_scale + f32::EPSILON)
+                        .unwrap_or(PLAYER_SPEED_SCALE_STEPS[0]);
+                }
+                MenuButtonAction::TogglePlayerTrail => {
+                    settings.player_trail.value = !settings.player_trail.value;
+                }
+                MenuButtonAction::ToggleRibbonTrails => {
+                    settings.ribbon_trails.value = !settings.ribbon_trails.value;
+                }
+                MenuButtonAction::ToggleGrowingBullets => {
+                    settings.growing_bullets.value = !settings.growing_bullets.value;
+                }
+                MenuButtonAction::ToggleDangerHighlight => {
+                    settings.danger_highlight.value = !settings.danger_highlight.value;
+                }
+                MenuButtonAction::CycleArenaAspect => {
+                    settings.arena_aspect.value = match settings.arena_aspect.value {
+                        ArenaAspect::Full => ArenaAspect::Widescreen,
+                        ArenaAspect::Widescreen => ArenaAspect::Standard,
+                        ArenaAspect::Standard => ArenaAspect::Full,
+                    };
+                }
+                MenuButtonAction::CycleArenaFraction => {
+                    settings.arena_fraction.value = ARENA_FRACTION_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.arena_fraction.value + f32::EPSILON)
+                        .unwrap_or(ARENA_FRACTION_STEPS[0]);
+                }
+                MenuButtonAction::CycleHitboxScale => {
+                    settings.hitbox_scale.value = HITBOX_SCALE_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.hitbox_scale.value + f32::EPSILON)
+                        .unwrap_or(HITBOX_SCALE_STEPS[0]);
+                }
+                MenuButtonAction::ToggleThreatIndicators => {
+                    settings.threat_indicators.value = !settings.threat_indicators.value;
+                }
+                MenuButtonAction::ToggleVelocityVectors => {
+                    settings.velocity_vectors.value = !settings.velocity_vectors.value;
+                }
+                MenuButtonAction::CycleAimDistance => {
+                    settings.aim_distance.value =
+                        if settings.aim_distance.value >= GAMEPAD_AIM_DISTANCE_FAR {
+                            GAMEPAD_AIM_DISTANCE
+                        } else {
+                            GAMEPAD_AIM_DISTANCE_FAR
+                        };
+                }
+                MenuButtonAction::CycleAimAssist => {
+                    settings.aim_assist.value = if settings.aim_assist.value >= 1.0 {
+                        0.0
+                    } else {
+                        (settings.aim_assist.value + AIM_ASSIST_STEP).min(1.0)
+                    };
+                }
+                MenuButtonAction::CycleAimMode => {
+                    settings.aim_mode.value = match settings.aim_mode.value {
+                        AimMode::Relative => AimMode::Absolute,
+                        AimMode::Absolute => AimMode::Relative,
+                    };
+                }
+                MenuButtonAction::ToggleFrameRateAim => {
+                    settings.frame_rate_aim.value = !settings.frame_rate_aim.value;
+                }
+                MenuButtonAction::CycleFireMode => {
+                    settings.fire_mode.value = match settings.fire_mode.value {
+                        FireMode::Auto => FireMode::Manual,
+                        FireMode::Manual => FireMode::Auto,
+                    };
+                }
+                MenuButtonAction::ToggleTapFire => {
+                    settings.tap_fire.value = !settings.tap_fire.value;
+                }
+                MenuButtonAction::CycleFireKey => {
+                    let next_key = match FireKey::from(settings.key_bindings.fire_key) {
+                        FireKey::Space => FireKey::KeyF,
+                        FireKey::KeyF => FireKey::KeyX,
+                        FireKey::KeyX => FireKey::Space,
+                    };
+                    settings.key_bindings.fire_key = next_key.into();
+                }
+                MenuButtonAction::CyclePauseButton => {
+                    let next_button = match PauseButton::from(settings.key_bindings.pause_gamepad) {
+                        PauseButton::Start => PauseButton::Select,
+                        PauseButton::Select => PauseButton::LeftThumb,
+                        PauseButton::LeftThumb => PauseButton::RightThumb,
+                        PauseButton::RightThumb => PauseButton::Start,
+                    };
+                    settings.key_bindings.pause_gamepad = next_button.into();
+                }
+                MenuButtonAction::ToggleGuidePauses => {
+                    settings.key_bindings.pause_on_guide = !settings.key_bindings.pause_on_guide;
+                }
+                MenuButtonAction::CycleBulletColor => {
+                    settings.bullet_color.value = match settings.bullet_color.value {
+                        BulletColorMode::Oscillate => BulletColorMode::Static {
+                            hue: BULLET_STATIC_HUES[0],
+                        },
+                        BulletColorMode::Static { hue } => BULLET_STATIC_HUES
+                            .into_iter()
+                            .skip_while(|step| (step - hue).abs() > f32::EPSILON)
+                            .nth(1)
+                            .map_or(BulletColorMode::Oscillate, |hue| BulletColorMode::Static {
+                                hue,
+                            }),
+                    };
+                }
+                MenuButtonAction::CycleShotPattern => {
+                    *settings.shot_pattern = match *settings.shot_pattern {
+                        ShotPattern::Single => ShotPattern::Spread {
+                            count: SHOT_SPREAD_COUNT,
+                            angle: SHOT_SPREAD_ANGLE,
+                        },
+                        ShotPattern::Spread { .. } => ShotPattern::Single,
+                    };
+                }
+                MenuButtonAction::ToggleReticle => {
+                    settings.reticle.visible = !settings.reticle.visible;
+                }
+                MenuButtonAction::CycleReticleShape => {
+                    settings.reticle.shape = match settings.reticle.shape {
+                        ReticleShape::Dot => ReticleShape::Crosshair,
+                        ReticleShape::Crosshair => ReticleShape::Dot,
+                    };
+                }
+                MenuButtonAction::CycleReticleSize => {
+                    settings.reticle.size = RETICLE_SIZE_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.reticle.size + f32::EPSILON)
+                        .unwrap_or(RETICLE_SIZE_STEPS[0]);
+                }
+                MenuButtonAction::CycleFontScale => {
+                    settings.font.scale = FONT_SCALE_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.font.scale + f32::EPSILON)
+                        .unwrap_or(FONT_SCALE_STEPS[0]);
+                }
+                MenuButtonAction::CycleBrightness => {
+                    settings.brightness.value = BRIGHTNESS_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.brightness.value + f32::EPSILON)
+                        .unwrap_or(BRIGHTNESS_STEPS[0]);
+                }
+                MenuButtonAction::ToggleSessionStats => {
+                    settings.session_stats.value = !settings.session_stats.value;
+                }
+                MenuButtonAction::CycleTimerPrecision => {
+                    settings.timer_precision.value = match settings.timer_precision.value {
+                        TimerPrecision::Centi => TimerPrecision::Milli,
+                        TimerPrecision::Milli => TimerPrecision::Centi,
+                    };
+                }
+                MenuButtonAction::CycleMaxRunSeconds => {
+                    settings.max_run_seconds.value = MAX_RUN_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.max_run_seconds.value + f32::EPSILON)
+                        .unwrap_or(MAX_RUN_STEPS[0]);
+                }
+                MenuButtonAction::CycleSuddenDeath => {
+                    settings.sudden_death.threshold = SUDDEN_DEATH_STEPS
+                        .into_iter()
+                        .find(|step| *step > settings.sudden_death.threshold + f32::EPSILON)
+                        .unwrap_or(SUDDEN_DEATH_STEPS[0]);
+                }
+                MenuButtonAction::CyclePresentMode => {
+                    settings.present_mode.value = match settings.present_mode.value {
+                        PresentMode::AutoVsync => PresentMode::AutoNoVsync,
+                        PresentMode::AutoNoVsync => PresentMode::Mailbox,
+                        _ => PresentMode::AutoVsync,
+                    };
+                }
+            }
+        }
+    }
+}
+
+fn control_device_text(device: ControlDevice) -> &'static str {
+    match device {
+        ControlDevice::Keyboard => "Keyboard",
+        ControlDevice::Gamepad => "Gamepad",
+        ControlDevice::Mouse => "Mouse",
+    }
+}
+
+fn update_control_device_display(
+    primary_device: Res<PrimaryControlDevice>,
+    display: Query<&mut Text, With<ControlDeviceDisplay>>,
+) {
+    if !primary_device.is_changed() {
+        return;
+    }
+
+    for mut text in display {
+        text.0 = control_device_text(primary_device.value).to_string();
+    }
+}
+
+fn seed_label(seed_entry: &SeedEntry) -> String {
+    match seed_entry.value {
+        Some(seed) => format!("Seed: {}", seed),
+        None => "Seed: Random".to_string(),
+    }
+}
+
+// while the seed button is selected, digits typed on the keyboard edit the seed
+fn edit_seed_entry(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut seed_entry: ResMut<SeedEntry>,
+    labels: Query<(&mut Text, &ChildOf), With<SeedLabel>>,
+    selected: Query<(), With<SelectedOption>>,
+) {
+    const DIGIT_KEYS: [KeyCode; 10] = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+
+    for (mut text, parent) in labels {
+        if selected.contains(parent.parent()) {
+            let mut digits = seed_entry
+                .value
+                .map(|seed| seed.to_string())
+                .unwrap_or_default();
+            for (digit, key) in DIGIT_KEYS.iter().enumerate() {
+                if keyboard_input.just_pressed(*key) && digits.len() < SEED_MAX_DIGITS {
+                    digits.push_str(&digit.to_string());
+                }
+            }
+            if keyboard_input.just_pressed(KeyCode::Backspace) {
+                digits.pop();
+            }
+
+            let value = digits.parse().ok();
+            if value != seed_entry.value {
+                seed_entry.value = value;
+            }
+        }
+
+        if seed_entry.is_changed() {
+            text.0 = seed_label(&seed_entry);
+        }
+    }
+}
+
+fn start_run_seed(
+    seed_entry: Res<SeedEntry>,
+    mut current_seed: ResMut<CurrentSeed>,
+    mut randomness: ResMut<RandomSource>,
+) {
+    current_seed.value = seed_entry.value.unwrap_or_else(rand::random);
+    randomness.0 = ChaCha8Rng::seed_from_u64(current_seed.value);
+}
+
+fn update_setting_labels(settings: GameSettings, labels: Query<(&SettingLabel, &mut Text)>) {
+    if !settings.any_changed() {
+        return;
+    }
+
+    for (setting, mut text) in labels {
+        text.0 = settings.label(*setting);
+    }
+}
+
+// shared look of the buttons within a single menu, the sizes differ between menus
+struct MenuStyle {
+    font: Handle<Font>,
+    button_node: Node,
+    button_text_font: TextFont,
+}
+
+impl MenuStyle {
+    fn new(font: Handle<Font>, width: Val, height: Val, margin: Val, font_size: f32) -> Self {
+        MenuStyle {
+            button_node: Node {
+                width,
+                height,
+                margin: UiRect::all(margin),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            button_text_font: TextFont {
+                font: font.clone(),
+                font_size,
+                ..default()
+            },
+            font,
+        }
+    }
+}
+
+// spawns a full screen node centering a vertical layout box, and returns the layout box
+// the box doubles as the ButtonsHolder, so any buttons spawned into it can be navigated
+fn spawn_menu_root(commands: &mut Commands, state: AppState) -> Entity {
+    let root = commands
+        .spawn((
+            DespawnOnExit(state),
+            Node {
+                width: percent(100),
+                height: percent(100),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+        ))
+        .id();
+
+    commands
+        .spawn((
+            ChildOf(root),
+            // vertical layout box
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ButtonsHolder,
+        ))
+        .id()
+}
+
+fn spawn_menu_text(
+    commands: &mut Commands,
+    parent: Entity,
+    font: &Handle<Font>,
+    text: impl Into<String>,
+    font_size: f32,
+    margin: Val,
+) -> Entity {
+    commands
+        .spawn((
+            ChildOf(parent),
+            Text::new(text),
+            TextFont {
+                font: font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+            Node {
+                margin: UiRect::all(margin),
+                ..default()
+            },
+        ))
+        .id()
+}
+
+fn spawn_corner_text(
+    commands: &mut Commands,
+    state: AppState,
+    font: &Handle<Font>,
+    text: impl Into<String>,
+    font_size: f32,
+    placement: Node,
+) -> Entity {
+    commands
+        .spawn((
+            DespawnOnExit(state),
+            Text::new(text),
+            TextFont {
+                font: font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                ..placement
+            },
+        ))
+        .id()
+}
+
+// returns the entity of the button's label, so callers can tag it (e.g. with a SettingLabel)
+fn spawn_menu_button(
+    commands: &mut Commands,
+    parent: Entity,
+    style: &MenuStyle,
+    label: impl Into<String>,
+    action: MenuButtonAction,
+    selected: bool,
+) -> Entity {
+    let button = commands
+        .spawn((
+            ChildOf(parent),
+            Button,
+            style.button_node.clone(),
+            BackgroundColor(IDLE_BUTTON),
+            action,
+        ))
+        .id();
+    if selected {
+        commands.entity(button).insert(SelectedOption);
+    }
+
+    commands
+        .spawn((
+            ChildOf(button),
+            Text::new(label),
+            style.button_text_font.clone(),
+            TextColor(TEXT_COLOR),
+        ))
+        .id()
+}
+
+fn spawn_menu_background(
+    mut commands: Commands,
+    bullet_data: Res<BulletRenderComponents>,
+    display_properties: Res<DisplayProperties>,
+    mut randomness: ResMut<RandomSource>,
+) {
+    let circle = Circle::new(1.0);
+    for _ in 0..MENU_BACKGROUND_BALL_COUNT {
+        let rng = &mut randomness.0;
+        let position = Vec3::new(
+            rng.random_range(-display_properties.half_w..display_properties.half_w),
+            rng.random_range(-display_properties.half_h..display_properties.half_h),
+            MENU_BACKGROUND_LAYER,
+        );
+        let direction = circle.sample_boundary(rng);
+        commands.spawn((
+            MenuBackgroundBall,
+            ScreenEdgeBouncer {
+                velocity: direction.extend(0.0) * MENU_BACKGROUND_BALL_SPEED,
+            },
+            Mesh2d(bullet_data.mesh.clone()),
+            MeshMaterial2d(bullet_data.material.clone()),
+            Transform::from_translation(position),
+            DespawnOnExit(AppState::Menu),
+        ));
+    }
+}
+
+fn start_loading(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    font_settings: Res<FontSettings>,
+) {
+    commands.insert_resource(UiFont {
+        handle: asset_server.load(&font_settings.path),
+    });
+    let mut handles = Vec::new();
+    for path in PRELOADED_SOUNDS {
+        handles.push(asset_server.load::<AudioSource>(path).untyped());
+    }
+    commands.insert_resource(PreloadedAssets { handles });
+
+    // the main font is one of the things being loaded, so this uses the built-in one
+    commands.spawn((
+        DespawnOnExit(AppState::Loading),
+        Node {
+            width: percent(100),
+            height: percent(100),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        children![(LoadingText, Text::new("Loading..."), TextColor(TEXT_COLOR))],
+    ));
+}
+
+fn check_loading(
+    asset_server: Res<AssetServer>,
+    preloaded: Res<PreloadedAssets>,
+    mut ui_font: ResMut<UiFont>,
+    mut game_state: ResMut<NextState<AppState>>,
+    mut text: Single<&mut Text, With<LoadingText>>,
+) {
+    let mut all_loaded = true;
+    // the default handle is Bevy's built-in font, which never has to load
+    if ui_font.handle != Handle::default() {
+        match asset_server.load_state(ui_font.handle.id()) {
+            LoadState::Loaded => {}
+            LoadState::Failed(error) => {
+                warn!("failed to load the font, using the built-in one: {}", error);
+                ui_font.handle = Handle::default();
+            }
+            _ => all_loaded = false,
+        }
+    }
+    for handle in &preloaded.handles {
+        match asset_server.load_state(handle.id()) {
+            LoadState::Loaded => {}
+            LoadState::Failed(error) => {
+                // stay on the loading screen, the game would be silent without the asset
+                text.0 = format!("Failed to load assets: {}", error);
+                return;
+            }
+            _ => all_loaded = false,
+        }
+    }
+
+    if all_loaded {
+        game_state.set(AppState::Menu);
+    }
+}
+
+fn main_menu_setup(
+    mut commands: Commands,
+    window: Single<&Window>,
+    ui_font: Res<UiFont>,
+    mut settings: GameSettings,
+    primary_device: Res<PrimaryControlDevice>,
+    seed_entry: Res<SeedEntry>,
+    session_stats: Res<SessionStats>,
+) {
+    let w = window.resolution.physical_width();
+    let h = window.resolution.physical_height();
+    println!("{}x{}", w, h);
+
+    let font = ui_font.handle.clone();
+    let style = MenuStyle::new(
+        font.clone(),
+        px(w / 4),
+        px(h / 10),
+        px(h / 64),
+        font_size(h, 16, &settings.font),
+    );
+
+    spawn_corner_text(
+        &mut commands,
+        AppState::Menu,
+        &font,
+        "LunaticDancer, 2025",
+        font_size(h, 20, &settings.font),
+        Node {
+            bottom: px(8),
+            left: px(8),
+            ..default()
+        },
+    );
+    let device_display = spawn_corner_text(
+        &mut commands,
+        AppState::Menu,
+        &font,
+        control_device_text(primary_device.value),
+        font_size(h, 20, &settings.font),
+        Node {
+            top: px(8),
+            right: px(8),
+            ..default()
+        },
+    );
+    commands.entity(device_display).insert(ControlDeviceDisplay);
+    if settings.session_stats.value && session_stats.runs_played > 0 {
+        spawn_corner_text(
+            &mut commands,
+            AppState::Menu,
+            &font,
+            session_stats_text(&session_stats, settings.timer_precision.value),
+            font_size(h, 20, &settings.font),
+            Node {
+                top: px(8),
+                left: px(8),
+                ..default()
+            },
+        );
+    }
+    spawn_corner_text(
+        &mut commands,
+        AppState::Menu,
+        &font,
+        "v: 1.0.1, made with Bevy",
+        font_size(h, 20, &settings.font),
+        Node {
+            bottom: px(8),
+            right: px(8),
+            ..default()
+        },
+    );
+
+    let menu = spawn_menu_root(&mut commands, AppState::Menu);
+    // game title
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        "DODGE_BALL",
+        font_size(h, 5, &settings.font),
+        px(h / 32),
+    );
+    spawn_menu_button(
+        &mut commands,
+        menu,
+        &style,
+        "Play",
+        MenuButtonAction::Play,
+        true,
+    );
+    let game_mode_label = spawn_menu_button(
+        &mut commands,
+        menu,
+        &style,
+        settings.label(SettingLabel::GameMode),
+        MenuButtonAction::CycleGameMode,
+        false,
+    );
+    commands
+        .entity(game_mode_label)
+        .insert(SettingLabel::GameMode);
+    let seed_label_entity = spawn_menu_button(
+        &mut commands,
+        menu,
+        &style,
+        seed_label(&seed_entry),
+        MenuButtonAction::CycleSeed,
+        false,
+    );
+    commands.entity(seed_label_entity).insert(SeedLabel);
+    spawn_menu_button(
+        &mut commands,
+        menu,
+        &style,
+        "Settings",
+        MenuButtonAction::ToSettings,
+        false,
+    );
+    spawn_menu_button(
+        &mut commands,
+        menu,
+        &style,
+        "Quit",
+        MenuButtonAction::Quit,
+        false,
+    );
+
+    // marked as seen right away, the regular config save picks it up when the menu is left
+    if !settings.seen_tutorial.value {
+        settings.seen_tutorial.value = true;
+        spawn_tutorial_overlay(&mut commands, &font, h, &settings);
+    }
+}
+
+fn spawn_tutorial_overlay(
+    commands: &mut Commands,
+    font: &Handle<Font>,
+    h: u32,
+    settings: &GameSettings,
+) {
+    let bindings = &settings.key_bindings;
+    let lines = [
+        "CONTROLS".to_string(),
+        "Move: WASD or arrows, left stick".to_string(),
+        format!(
+            "Aim: mouse, {} {} {} {}, right stick",
+            key_label(bindings.aim_up),
+            key_label(bindings.aim_left),
+            key_label(bindings.aim_down),
+            key_label(bindings.aim_right)
+        ),
+        format!(
+            "Fire: {}, {}, {}",
+            FireKey::from(bindings.fire_key).label(),
+            mouse_button_label(bindings.fire_mouse),
+            gamepad_button_label(bindings.fire_gamepad)
+        ),
+        format!(
+            "Bomb: {}, {}, {}",
+            key_label(bindings.bomb_key),
+            mouse_button_label(bindings.bomb_mouse),
+            gamepad_button_label(bindings.bomb_gamepad)
+        ),
+        format!(
+            "Focus: {}, {}",
+            key_label(bindings.focus_key),
+            gamepad_button_label(bindings.focus_gamepad)
+        ),
+        format!(
+            "Pause: Esc, {}",
+            PauseButton::from(bindings.pause_gamepad).label()
+        ),
+        String::new(),
+        "Press any button to continue".to_string(),
+    ];
+
+    let overlay = commands
+        .spawn((
+            TutorialOverlay,
+            DespawnOnExit(AppState::Menu),
+            Node {
+                position_type: PositionType::Absolute,
+                width: percent(100),
+                height: percent(100),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.85)),
+            // keeps a dismissing click from also pressing the button underneath
+            FocusPolicy::Block,
+            GlobalZIndex(1),
+        ))
+        .id();
+    for line in lines {
+        spawn_menu_text(
+            commands,
+            overlay,
+            font,
+            line,
+            font_size(h, 20, &settings.font),
+            px(4),
+        );
+    }
+}
+
+// any key, mouse button or gamepad button, the same press never reaches the menu underneath
+fn dismiss_tutorial(
+    mut commands: Commands,
+    overlay: Single<Entity, With<TutorialOverlay>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+) {
+    if keyboard_input.get_just_pressed().next().is_some()
+        || mouse_input.get_just_pressed().next().is_some()
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.get_just_pressed().next().is_some())
+    {
+        commands.entity(*overlay).despawn();
+    }
+}
+
+fn settings_menu_setup(
+    mut commands: Commands,
+    window: Single<&Window>,
+    ui_font: Res<UiFont>,
+    settings: GameSettings,
+) {
+    let w = window.resolution.physical_width();
+    let h = window.resolution.physical_height();
+
+    let font = ui_font.handle.clone();
+    let style = MenuStyle::new(
+        font.clone(),
+        px(w / 3),
+        px(h / 12),
+        px(h / 96),
+        font_size(h, 20, &settings.font),
+    );
+
+    let menu = spawn_menu_root(&mut commands, AppState::Settings);
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        "SETTINGS",
+        font_size(h, 10, &settings.font),
+        px(12),
+    );
+    // option list, flowing into extra columns once it runs out of height
+    let list = commands
+        .spawn((
+            ChildOf(menu),
+            Node {
+                flex_direction: FlexDirection::Column,
+                flex_wrap: FlexWrap::Wrap,
+                align_items: AlignItems::Center,
+                align_content: AlignContent::Center,
+                max_height: Val::Vh(75.0),
+                ..default()
+            },
+            ButtonsHolder,
+        ))
+        .id();
+
+    let options = [
+        (
+            MenuButtonAction::ToggleReduceMotion,
+            SettingLabel::ReduceMotion,
+        ),
+        (
+            MenuButtonAction::CycleParticleQuality,
+            SettingLabel::ParticleQuality,
+        ),
+        (MenuButtonAction::ToggleBloom, SettingLabel::Bloom),
+        (
+            MenuButtonAction::ToggleBackgroundTint,
+            SettingLabel::BackgroundTint,
+        ),
+        (
+            MenuButtonAction::ToggleDeathCinematic,
+            SettingLabel::DeathCinematic,
+        ),
+        (MenuButtonAction::TogglePractice, SettingLabel::Practice),
+        (MenuButtonAction::ToggleAssist, SettingLabel::Assist),
+        (MenuButtonAction::ToggleRevive, SettingLabel::Revive),
+        (
+            MenuButtonAction::CyclePlayerSpeed,
+            SettingLabel::PlayerSpeed,
+        ),
+        (
+            MenuButtonAction::TogglePlayerTrail,
+            SettingLabel::PlayerTrail,
+        ),
+        (
+            MenuButtonAction::ToggleRibbonTrails,
+            SettingLabel::RibbonTrails,
+        ),
+        (
+            MenuButtonAction::CycleHitboxScale,
+            SettingLabel::HitboxScale,
+        ),
+        (MenuButtonAction::ToggleWaves, SettingLabel::Waves),
+        (MenuButtonAction::CycleElasticity, SettingLabel::Elasticity),
+        (
+            MenuButtonAction::ToggleGrowingBullets,
+            SettingLabel::GrowingBullets,
+        ),
+        (MenuButtonAction::ToggleHeatmap, SettingLabel::Heatmap),
+        (
+            MenuButtonAction::ToggleThreatIndicators,
+            SettingLabel::ThreatIndicators,
+        ),
+        (
+            MenuButtonAction::ToggleVelocityVectors,
+            SettingLabel::VelocityVectors,
+        ),
+        (
+            MenuButtonAction::ToggleDangerHighlight,
+            SettingLabel::DangerHighlight,
+        ),
+        (
+            MenuButtonAction::CycleAimDistance,
+            SettingLabel::AimDistance,
+        ),
+        (MenuButtonAction::CycleAimAssist, SettingLabel::AimAssist),
+        (MenuButtonAction::CycleAimMode, SettingLabel::AimMode),
+        (
+            MenuButtonAction::ToggleFrameRateAim,
+            SettingLabel::FrameRateAim,
+        ),
+        (
+            MenuButtonAction::ToggleReticle,
+            SettingLabel::ReticleVisible,
+        ),
+        (
+            MenuButtonAction::CycleReticleShape,
+            SettingLabel::ReticleShape,
+        ),
+        (
+            MenuButtonAction::CycleReticleSize,
+            SettingLabel::ReticleSize,
+        ),
+        (MenuButtonAction::CycleFontScale, SettingLabel::FontScale),
+        (MenuButtonAction::CycleBrightness, SettingLabel::Brightness),
+        (MenuButtonAction::CycleFireMode, SettingLabel::FireMode),
+        (MenuButtonAction::ToggleTapFire, SettingLabel::TapFire),
+        (MenuButtonAction::CycleFireKey, SettingLabel::FireKey),
+        (
+            MenuButtonAction::CycleShotPattern,
+            SettingLabel::ShotPattern,
+        ),
+        (
+            MenuButtonAction::CycleBulletColor,
+            SettingLabel::BulletColor,
+        ),
+        (
+            MenuButtonAction::CyclePauseButton,
+            SettingLabel::PauseButton,
+        ),
+        (
+            MenuButtonAction::ToggleGuidePauses,
+            SettingLabel::GuidePauses,
+        ),
+        (
+            MenuButtonAction::TogglePauseOnDisconnect,
+            SettingLabel::PauseOnDisconnect,
+        ),
+        (
+            MenuButtonAction::CycleRumbleStrength,
+            SettingLabel::RumbleStrength,
+        ),
+        (
+            MenuButtonAction::CycleScreenshakeScale,
+            SettingLabel::ScreenshakeScale,
+        ),
+        (
+            MenuButtonAction::CycleCameraFollow,
+            SettingLabel::CameraFollow,
+        ),
+        (
+            MenuButtonAction::CycleMaxRunSeconds,
+            SettingLabel::MaxRunSeconds,
+        ),
+        (
+            MenuButtonAction::CycleSuddenDeath,
+            SettingLabel::SuddenDeath,
+        ),
+        (
+            MenuButtonAction::CycleTimerPrecision,
+            SettingLabel::TimerPrecision,
+        ),
+        (
+            MenuButtonAction::ToggleSessionStats,
+            SettingLabel::SessionStats,
+        ),
+        (
+            MenuButtonAction::CycleArenaAspect,
+            SettingLabel::ArenaAspect,
+        ),
+        (
+            MenuButtonAction::CycleArenaFraction,
+            SettingLabel::ArenaFraction,
+        ),
+        (
+            MenuButtonAction::CyclePresentMode,
+            SettingLabel::PresentMode,
+        ),
+    ];
+    for (index, (action, setting)) in options.into_iter().enumerate() {
+        let label = spawn_menu_button(
+            &mut commands,
+            list,
+            &style,
+            settings.label(setting),
+            action,
+            index == 0,
+        );
+        commands.entity(label).insert(setting);
+    }
+    spawn_menu_button(
+        &mut commands,
+        list,
+        &style,
+        "Data",
+        MenuButtonAction::ToData,
+        false,
+    );
+    spawn_menu_button(
+        &mut commands,
+        list,
+        &style,
+        "Back",
+        MenuButtonAction::ToMenu,
+        false,
+    );
+}
+
+fn best_run_summary(best_ghost: &BestGhost) -> String {
+    if best_ghost.samples.is_empty() && best_ghost.duration <= 0.0 {
+        return "Best run: none".to_string();
+    }
+    format!("Best run: {:.2}s", best_ghost.duration)
+}
+
+fn data_menu_setup(
+    mut commands: Commands,
+    window: Single<&Window>,
+    ui_font: Res<UiFont>,
+    font_settings: Res<FontSettings>,
+    best_ghost: Res<BestGhost>,
+    mut data_confirm: ResMut<DataConfirm>,
+) {
+    let w = window.resolution.physical_width();
+    let h = window.resolution.physical_height();
+    data_confirm.armed = None;
+
+    let font = ui_font.handle.clone();
+    let style = MenuStyle::new(
+        font.clone(),
+        px(w / 3),
+        px(h / 10),
+        px(h / 96),
+        font_size(h, 20, &font_settings),
+    );
+
+    let menu = spawn_menu_root(&mut commands, AppState::Data);
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        "DATA",
+        font_size(h, 10, &font_settings),
+        px(12),
+    );
+    let summary = spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        best_run_summary(&best_ghost),
+        font_size(h, 24, &font_settings),
+        px(4),
+    );
+    commands.entity(summary).insert(BestRunSummary);
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        format!("Stored in {} and {}", GHOST_FILE_PATH, CONFIG_FILE_PATH),
+        font_size(h, 24, &font_settings),
+        px(4),
+    );
+
+    let list = commands
+        .spawn((
+            ChildOf(menu),
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ButtonsHolder,
+        ))
+        .id();
+    for (action, data_action) in [
+        (MenuButtonAction::ResetBestRun, DataAction::ResetBestRun),
+        (MenuButtonAction::ResetSettings, DataAction::ResetSettings),
+    ] {
+        let label = spawn_menu_button(
+            &mut commands,
+            list,
+            &style,
+            data_action.label(false),
+            action,
+            data_action == DataAction::ResetBestRun,
+        );
+        commands.entity(label).insert(data_action);
+    }
+    spawn_menu_button(
+        &mut commands,
+        list,
+        &style,
+        "Back",
+        MenuButtonAction::ToSettings,
+        false,
+    );
+}
+
+// any input from a controller claims it again, which is as good as a reconnect
+fn update_disconnect_notice(
+    active_gamepad: Res<ActiveGamepad>,
+    notices: Query<&mut Text, With<DisconnectNotice>>,
+) {
+    if !active_gamepad.is_changed() || active_gamepad.value.is_none() {
+        return;
+    }
+
+    for mut text in notices {
+        text.0 = "Controller reconnected".to_string();
+    }
+}
+
+fn clear_controller_disconnected(mut disconnected: ResMut<ControllerDisconnected>) {
+    disconnected.value = false;
+}
+
+fn update_data_labels(
+    mut commands: Commands,
+    data_confirm: Res<DataConfirm>,
+    best_ghost: Res<BestGhost>,
+    mut labels: Query<(&DataAction, &mut Text), Without<BestRunSummary>>,
+    mut summary: Query<&mut Text, With<BestRunSummary>>,
+    buttons: Query<(Entity, &MenuButtonAction), With<Button>>,
+) {
+    if data_confirm.is_changed() {
+        for (action, mut text) in &mut labels {
+            text.0 = action
+                .label(data_confirm.armed == Some(*action))
+                .to_string();
+        }
+    }
+    if best_ghost.is_changed() {
+        for mut text in &mut summary {
+            text.0 = best_run_summary(&best_ghost);
+        }
+        // there's nothing to reset without a best run, the selection moves on to the next button
+        for (button, action) in &buttons {
+            if !matches!(action, MenuButtonAction::ResetBestRun) {
+                continue;
+            }
+            if best_ghost.duration > 0.0 {
+                commands.entity(button).remove::<Disabled>();
+            } else {
+                commands
+                    .entity(button)
+                    .insert(Disabled)
+                    .remove::<SelectedOption>();
+            }
+        }
+    }
+}
+
+fn pause_menu_setup(
+    mut commands: Commands,
+    window: Single<&Window>,
+    ui_font: Res<UiFont>,
+    font_settings: Res<FontSettings>,
+    disconnected: Res<ControllerDisconnected>,
+) {
+    let w = window.resolution.physical_width();
+    let h = window.resolution.physical_height();
+
+    let font = ui_font.handle.clone();
+    let style = MenuStyle::new(
+        font.clone(),
+        px(w / 4),
+        px(h / 8),
+        px(8),
+        font_size(h, 14, &font_settings),
+    );
+
+    let menu = spawn_menu_root(&mut commands, AppState::Paused);
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        "PAUSED",
+        font_size(h, 10, &font_settings),
+        px(12),
+    );
+    if disconnected.value {
+        let notice = spawn_menu_text(
+            &mut commands,
+            menu,
+            &font,
+            "Controller disconnected",
+            font_size(h, 20, &font_settings),
+            px(8),
+        );
+        commands.entity(notice).insert(DisconnectNotice);
+    }
+    spawn_menu_button(
+        &mut commands,
+        menu,
+        &style,
+        "Resume",
+        MenuButtonAction::Resume,
+        true,
+    );
+    spawn_menu_button(
+        &mut commands,
+        menu,
+        &style,
+        "Restart",
+        MenuButtonAction::Restart,
+        false,
+    );
+    spawn_menu_button(
+        &mut commands,
+        menu,
+        &style,
+        "To Menu",
+        MenuButtonAction::ToMenu,
+        false,
+    );
+    spawn_menu_button(
+        &mut commands,
+        menu,
+        &style,
+        "Quit",
+        MenuButtonAction::Quit,
+        false,
+    );
+}
+
+fn game_over_screen_setup(
+    mut commands: Commands,
+    window: Single<&Window>,
+    ui_font: Res<UiFont>,
+    font_settings: Res<FontSettings>,
+    current_seed: Res<CurrentSeed>,
+    score: Res<Score>,
+    max_run: Res<MaxRunSeconds>,
+    session_stats: Res<SessionStats>,
+    session_stats_enabled: Res<SessionStatsEnabled>,
+    precision: Res<TimerPrecisionSetting>,
+) {
+    let h = window.resolution.physical_height();
+
+    let font = ui_font.handle.clone();
+
+    let capped = max_run.value > 0.0 && score.value >= max_run.value;
+    let menu = spawn_menu_root(&mut commands, AppState::GameOver);
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        if capped { "TIME'S UP" } else { "GAME OVER" },
+        font_size(h, 6, &font_settings),
+        px(12),
+    );
+    // can be replayed from the seed button on the main menu
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        format!("Seed: {}", current_seed.value),
+        font_size(h, 20, &font_settings),
+        px(12),
+    );
+    if session_stats_enabled.value {
+        spawn_menu_text(
+            &mut commands,
+            menu,
+            &font,
+            session_stats_text(&session_stats, precision.value),
+            font_size(h, 20, &font_settings),
+            px(12),
+        );
+    }
+    // continue prompt
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        "Press any button to continue",
+        font_size(h, 20, &font_settings),
+        px(12),
+    );
+}
+
+fn victory_screen_setup(
+    mut commands: Commands,
+    window: Single<&Window>,
+    ui_font: Res<UiFont>,
+    font_settings: Res<FontSettings>,
+) {
+    let h = window.resolution.physical_height();
+
+    let font = ui_font.handle.clone();
+
+    let menu = spawn_menu_root(&mut commands, AppState::Victory);
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        "YOU WIN",
+        font_size(h, 6, &font_settings),
+        px(12),
+    );
+    // continue prompt
+    spawn_menu_text(
+        &mut commands,
+        menu,
+        &font,
+        "Press any button to continue",
+        font_size(h, 20, &font_settings),
+        px(12),
+    );
+}
+
+fn gameplay_ui_setup(
+    mut commands: Commands,
+    window: Single<&Window>,
+    ui_font: Res<UiFont>,
+    font_settings: Res<FontSettings>,
+    practice_mode: Res<PracticeMode>,
+    assist_mode: Res<AssistMode>,
+    game_mode: Res<SelectedGameMode>,
+    best_ghost: Res<BestGhost>,
+    max_run: Res<MaxRunSeconds>,
+    precision: Res<TimerPrecisionSetting>,
+    sudden_death: Res<SuddenDeathConfig>,
+) {
+    let h = window.resolution.physical_height();
+
+    let font = ui_font.handle.clone();
+
+    if sudden_death.threshold > 0.0 {
+        commands.spawn((
+            DespawnOnEnter(AppState::Menu),
+            SuddenDeathWarning,
+            Text::new("SUDDEN DEATH"),
+            TextFont {
+                font: font.clone(),
+                font_size: font_size(h, 8, &font_settings),
+                ..default()
+            },
+            TextColor(SUDDEN_DEATH_WARNING_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                top: percent(40),
+                width: percent(100),
+                ..default()
+            },
+            TextLayout::new_with_justify(Justify::Center),
+            Visibility::Hidden,
+        ));
+    }
+
+    // survival has no goal, and a first endless run has no best to measure against yet
+    let has_target = match game_mode.value {
+        GameMode::Endless => best_ghost.duration > 0.0,
+        GameMode::Timed => true,
+        GameMode::Survival | GameMode::ScoreAttack => false,
+    };
+    if has_target {
+        commands.spawn((
+            DespawnOnEnter(AppState::Menu),
+            ProgressBar,
+            Node {
+                position_type: PositionType::Absolute,
+                top: px(0),
+                left: px(0),
+                width: percent(0),
+                height: px(PROGRESS_BAR_HEIGHT),
+                ..default()
+            },
+            BackgroundColor(PROGRESS_BAR_COLOR),
+        ));
+    }
+
+    let badges: Vec<&str> = [
+        (practice_mode.value, "PRACTICE"),
+        (assist_mode.value, "ASSIST"),
+    ]
+    .into_iter()
+    .filter_map(|(active, badge)| active.then_some(badge))
+    .collect();
+    if !badges.is_empty() {
+        commands.spawn((
+            DespawnOnEnter(AppState::Menu),
+            Text::new(badges.join("  ")),
+            TextFont {
+                font: font.clone(),
+                font_size: font_size(h, 20, &font_settings),
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                top: px(8),
+                left: px(8),
+                ..default()
+            },
+        ));
+    }
+
+    commands.spawn((
+        DespawnOnEnter(AppState::Menu),
+        Node {
+            width: percent(100),
+            height: percent(100),
+            align_items: AlignItems::Start,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        children![(
+            // vertical layout box
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            children![
+                // score display
+                // the fixed width keeps the left-aligned digits from drifting around as their glyphs change
+                (
+                    ScoreDisplay,
+                    Node {
+                        margin: UiRect::all(px(8)),
+                        width: px(precision.value.placeholder().len() as f32
+                            * SCORE_CHARACTER_WIDTH
+                            * font_size(h, 8, &font_settings)),
+                        ..default()
+                    },
+                    Text::new(precision.value.placeholder()),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: font_size(h, 8, &font_settings),
+                        ..default()
+                    },
+                    TextColor(TEXT_COLOR),
+                ),
+            ]
+        )],
+    ));
+
+    commands.spawn((
+        DespawnOnEnter(AppState::Menu),
+        BombDisplay,
+        Text::new(format!("Bombs: {}", BOMB_CHARGES)),
+        TextFont {
+            font: font.clone(),
+            font_size: font_size(h, 24, &font_settings),
+            ..default()
+        },
+        TextColor(TEXT_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: px(8),
+            left: px(8),
+            ..default()
+        },
+    ));
+
+    if max_run.value > 0.0 {
+        commands.spawn((
+            DespawnOnEnter(AppState::Menu),
+            RunCapDisplay,
+            Text::new(format!(
+                "Cap {}",
+                convert_time_to_text(max_run.value, precision.value)
+            )),
+            TextFont {
+                font: font.clone(),
+                font_size: font_size(h, 24, &font_settings),
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                top: px(8),
+                right: px(8),
+                ..default()
+            },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::gizmos::GizmoPlugin;
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+    use std::hint::black_box;
+    use std::time::Instant;
+
+    const TEST_SEED: u64 = 2137;
+    const BENCH_WARMUP_ROUNDS: u32 = 20;
+    const BENCH_ROUNDS: u32 = 200;
+
+    // the plugin as the game runs it, minus the real window, renderer and devices
+    // every update advances the clocks by exactly one fixed step
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            AssetPlugin::default(),
+            WindowPlugin::default(),
+            GizmoPlugin,
+        ));
+        app.init_asset::<Mesh>();
+        app.init_asset::<ColorMaterial>();
+        app.init_asset::<AudioSource>();
+        app.init_asset::<Font>();
+        // without the input plugin nothing clears the just pressed state, the helpers below do it by hand
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<ButtonInput<MouseButton>>();
+        app.add_message::<MouseMotion>();
+        app.add_message::<GamepadRumbleRequest>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+        app.add_plugins(DodgeBallPlugin);
+        // whatever config or ghost is lying around from earlier runs mustn't change the outcome
+        GameConfig::default().insert_resources(app.world_mut());
+        app.insert_resource(BestGhost {
+            duration: 0.0,
+            samples: Vec::new(),
+        });
+        app.insert_resource(RandomSource(ChaCha8Rng::seed_from_u64(TEST_SEED)));
+        // normally left behind by the loading screen, which the tests skip
+        app.insert_resource(UiFont {
+            handle: Handle::default(),
+        });
+        app.insert_resource(PreloadedAssets {
+            handles: Vec::new(),
+        });
+        app.update();
+        set_state(&mut app, AppState::Menu);
+        app
+    }
+
+    // the transition happens during the update, so the new state has run its first frame on return
+    fn set_state(app: &mut App, state: AppState) {
+        app.world_mut()
+            .resource_mut::<NextState<AppState>>()
+            .set(state);
+        app.update();
+    }
+
+    fn current_state(app: &App) -> AppState {
+        *app.world().resource::<State<AppState>>().get()
+    }
+
+    fn start_run(app: &mut App) {
+        set_state(app, AppState::InGame);
+        assert_eq!(current_state(app), AppState::InGame);
+    }
+
+    // held for exactly one update
+    fn tap_key(app: &mut App, key: KeyCode) {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(key);
+        app.update();
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release(key);
+        keyboard.clear();
+    }
+
+    fn tap_gamepad_button(app: &mut App, gamepad: Entity, button: GamepadButton) {
+        app.world_mut()
+            .get_mut::<Gamepad>(gamepad)
+            .unwrap()
+            .digital_mut()
+            .press(button);
+        app.update();
+        let mut gamepad = app.world_mut().get_mut::<Gamepad>(gamepad).unwrap();
+        gamepad.digital_mut().release(button);
+        gamepad.digital_mut().clear();
+    }
+
+    fn spawn_test_bullet(app: &mut App, position: Vec3, velocity: Vec3) -> Entity {
+        app.world_mut()
+            .run_system_once(
+                move |mut commands: Commands,
+                      bullet_data: Res<BulletRenderComponents>,
+                      trail_settings: Res<TrailSettings>| {
+                    commands
+                        .spawn(bullet_bundle(
+                            &bullet_data,
+                            &trail_settings,
+                            position,
+                            velocity,
+                        ))
+                        .id()
+                },
+            )
+            .unwrap()
+    }
+
+    // the resize message alone isn't enough, the bounds are read back from the window itself
+    fn resize_window(app: &mut App, width: u32, height: u32) {
+        let world = app.world_mut();
+        let mut windows = world.query::<(Entity, &mut Window)>();
+        let (window_entity, mut window) = windows.single_mut(world).unwrap();
+        window.resolution.set_physical_resolution(width, height);
+        world.write_message(WindowResized {
+            window: window_entity,
+            width: width as f32,
+            height: height as f32,
+        });
+    }
+
+    fn player_position(app: &mut App) -> Vec3 {
+        let mut player = app.world_mut().query_filtered::<&Transform, With<Player>>();
+        player.single(app.world()).unwrap().translation
+    }
+
+    #[derive(Resource, Default)]
+    struct DeathCount {
+        value: usize,
+    }
+
+    fn count_deaths(mut died_reader: MessageReader<PlayerDied>, mut count: ResMut<DeathCount>) {
+        count.value += died_reader.read().count();
+    }
+
+    // how far from the player's center every bullet started, measured before it had a chance to move
+    #[derive(Resource, Default)]
+    struct FreshBulletDistances {
+        values: Vec<f32>,
+    }
+
+    fn record_fresh_bullets(
+        bullets: Query<&Transform, Added<Bullet>>,
+        player: Single<&Transform, With<Player>>,
+        mut distances: ResMut<FreshBulletDistances>,
+    ) {
+        for bullet in &bullets {
+            distances.values.push(
+                bullet
+                    .translation
+                    .truncate()
+                    .distance(player.translation.truncate()),
+            );
+        }
+    }
+
+    // plays the script from the next fixed step on, a death stops the clock and with it the script
+    fn run_script(app: &mut App, steps: Vec<InputState>) {
+        let count = steps.len();
+        app.insert_resource(ScriptedInput { steps, next: 0 });
+        for _ in 0..count * 2 {
+            if app.world().resource::<ScriptedInput>().next >= count {
+                break;
+            }
+            app.update();
+        }
+    }
+
+    fn player_is_dead(app: &mut App) -> bool {
+        let world = app.world_mut();
+        world
+            .query_filtered::<(), (With<Player>, With<Dead>)>()
+            .iter(world)
+            .count()
+            == 1
+    }
+
+    fn bullet_count(app: &mut App) -> usize {
+        let world = app.world_mut();
+        world
+            .query_filtered::<(), With<Bullet>>()
+            .iter(world)
+            .count()
+    }
+
+    #[test]
+    fn bounce_particles_freeze_while_paused() {
+        let mut app = headless_app();
+        start_run(&mut app);
+        app.world_mut()
+            .resource_mut::<ParticleBatch>()
+            .bounce
+            .push(BounceParticle {
+                position: Vec2::ZERO,
+                lifetime: TRAIL_PARTICLE_LIFETIME,
+                velocity: Vec3::X,
+            });
+        tap_key(&mut app, KeyCode::Escape);
+        app.update();
+        assert_eq!(current_state(&app), AppState::Paused);
+
+        let snapshot = |app: &App| -> Vec<(Vec2, f32)> {
+            app.world()
+                .resource::<ParticleBatch>()
+                .bounce
+                .iter()
+                .map(|particle| (particle.position, particle.lifetime))
+                .collect()
+        };
+        let before = snapshot(&app);
+        assert!(!before.is_empty());
+        for _ in 0..32 {
+            app.update();
+        }
+        assert_eq!(snapshot(&app), before);
+    }
+
+    #[test]
+    fn shot_direction_is_a_unit_vector_when_aim_sits_on_the_player() {
+        let player = vec3(12.0, -30.0, PLAYER_LAYER);
+        for aim in [
+            player,
+            player.with_z(0.0),
+            player + Vec3::splat(f32::EPSILON),
+        ] {
+            let direction = shot_direction(player, aim);
+            assert!(direction.is_finite());
+            assert!((direction.length() - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn rapid_pause_presses_leave_state_and_clock_in_agreement() {
+        let mut app = headless_app();
+        start_run(&mut app);
+
+        // a single press toggles once, even with the transition frame in between
+        tap_key(&mut app, KeyCode::Escape);
+        for _ in 0..4 {
+            app.update();
+        }
+        assert_eq!(current_state(&app), AppState::Paused);
+        assert!(app.world().resource::<Time<Virtual>>().is_paused());
+
+        for _ in 0..9 {
+            tap_key(&mut app, KeyCode::Escape);
+        }
+        for _ in 0..32 {
+            app.update();
+        }
+        let state = current_state(&app);
+        assert!(matches!(state, AppState::InGame | AppState::Paused));
+        assert_eq!(
+            app.world().resource::<Time<Virtual>>().is_paused(),
+            state == AppState::Paused
+        );
+        assert!(!app.world().resource::<PauseInputBuffer>().pending);
+    }
+
+    // no resize message is ever sent here, like in a borderless fullscreen window
+    #[test]
+    fn display_properties_match_the_window_by_the_first_run_frame() {
+        let mut app = headless_app();
+        start_run(&mut app);
+        let mut windows = app.world_mut().query::<&Window>();
+        let window = windows.single(app.world()).unwrap();
+        let window_size = vec2(
+            window.resolution.physical_width() as f32,
+            window.resolution.physical_height() as f32,
+        );
+        let display = app.world().resource::<DisplayProperties>();
+        assert!(vec2(display.w, display.h).abs_diff_eq(window_size, 1e-3));
+    }
+
+    #[test]
+    fn mouse_motion_past_the_deadzone_switches_to_mouse() {
+        let mut app = headless_app();
+        let primary_device = |app: &App| app.world().resource::<PrimaryControlDevice>().value;
+        app.world_mut().resource_mut::<PrimaryControlDevice>().value = ControlDevice::Keyboard;
+
+        app.world_mut().write_message(MouseMotion {
+            delta: vec2(MOUSE_DEADZONE * 0.5, 0.0),
+        });
+        app.update();
+        assert_eq!(primary_device(&app), ControlDevice::Keyboard);
+
+        // purely leftward, a signed sum of the components would never get past the deadzone
+        app.world_mut().write_message(MouseMotion {
+            delta: vec2(-MOUSE_DEADZONE * 4.0, 0.0),
+        });
+        app.update();
+        assert_eq!(primary_device(&app), ControlDevice::Mouse);
+    }
+
+    #[test]
+    fn one_death_is_reported_once() {
+        let mut app = headless_app();
+        app.init_resource::<DeathCount>();
+        app.add_systems(Update, count_deaths);
+        start_run(&mut app);
+
+        // several bullets on the player at once, still a single hit
+        let position = player_position(&mut app);
+        for _ in 0..3 {
+            spawn_test_bullet(&mut app, position, Vec3::ZERO);
+        }
+        for _ in 0..16 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<DeathCount>().value, 1);
+        assert!(player_is_dead(&mut app));
+    }
+
+    #[test]
+    fn bullet_pairs_only_resolve_when_overlapping_and_approaching() {
+        let moving_right = ScreenEdgeBouncer { velocity: Vec3::X };
+        let moving_left = ScreenEdgeBouncer {
+            velocity: Vec3::NEG_X,
+        };
+        let collision_distance_squared = 10.0 * 10.0;
+
+        let (first, second, contact) = resolve_bullet_pair(
+            (vec3(-4.0, 0.0, 0.0), &moving_right),
+            (vec3(4.0, 0.0, 0.0), &moving_left),
+            collision_distance_squared,
+            1.0,
+        )
+        .unwrap();
+        assert_eq!(first, Vec3::NEG_X);
+        assert_eq!(second, Vec3::X);
+        assert_eq!(contact, Vec3::ZERO);
+
+        // too far apart to touch
+        assert!(
+            resolve_bullet_pair(
+                (vec3(-40.0, 0.0, 0.0), &moving_right),
+                (vec3(40.0, 0.0, 0.0), &moving_left),
+                collision_distance_squared,
+                1.0,
+            )
+            .is_none()
+        );
+        // still overlapping after the bounce, but already separating
+        assert!(
+            resolve_bullet_pair(
+                (vec3(-4.0, 0.0, 0.0), &moving_left),
+                (vec3(4.0, 0.0, 0.0), &moving_right),
+                collision_distance_squared,
+                1.0,
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn menu_navigation_skips_labels_and_disabled_buttons() {
+        let mut app = headless_app();
+        // without a best run the reset button is disabled
+        set_state(&mut app, AppState::Data);
+        app.update();
+
+        let world = app.world_mut();
+        let holder = world
+            .query_filtered::<Entity, With<ButtonsHolder>>()
+            .single(world)
+            .unwrap();
+        let label = world.spawn(Text::new("-")).id();
+        world.entity_mut(holder).insert_children(2, &[label]);
+        app.update();
+
+        let selected = |app: &mut App| {
+            let world = app.world_mut();
+            let mut selected =
+                world.query_filtered::<&MenuButtonAction, (With<Button>, With<SelectedOption>)>();
+            let actions: Vec<_> = selected.iter(world).collect();
+            assert_eq!(actions.len(), 1);
+            match actions[0] {
+                MenuButtonAction::ResetSettings => "reset settings",
+                MenuButtonAction::ToSettings => "back",
+                _ => "other",
+            }
+        };
+        // reset best run, reset settings, the label, back
+        assert_eq!(selected(&mut app), "reset settings");
+        tap_key(&mut app, KeyCode::ArrowDown);
+        app.update();
+        assert_eq!(selected(&mut app), "back");
+        tap_key(&mut app, KeyCode::ArrowDown);
+        app.update();
+        assert_eq!(selected(&mut app), "reset settings");
+        tap_key(&mut app, KeyCode::ArrowUp);
+        app.update();
+        assert_eq!(selected(&mut app), "back");
+    }
+
+    #[test]
+    fn app_exit_flushes_pending_saves() {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<PendingSaves>().config = true;
+        app.world_mut().write_message(AppExit::Success);
+        app.update();
+        assert!(!app.world().resource::<PendingSaves>().config);
+    }
+
+    #[test]
+    fn no_bullet_starts_inside_the_player() {
+        let mut app = headless_app();
+        app.init_resource::<FreshBulletDistances>();
+        app.add_systems(PostUpdate, record_fresh_bullets);
+        app.world_mut().resource_mut::<WavesEnabled>().value = true;
+        start_run(&mut app);
+
+        // a wave warning the player is standing right on top of
+        let position = player_position(&mut app);
+        app.world_mut().spawn((
+            WaveWarning {
+                timer: Timer::from_seconds(0.05, TimerMode::Once),
+                velocity: Vec3::X,
+            },
+            MeshMaterial2d::<ColorMaterial>::default(),
+            Transform::from_translation(position),
+        ));
+        // long enough for the first shots, which the player fires from its own position
+        for _ in 0..256 {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        let collision_distance = world
+            .run_system_once(
+                |display_properties: Res<DisplayProperties>,
+                 hitbox_scale: Res<HitboxScale>,
+                 bullet_growth: Res<BulletGrowth>,
+                 assist_mode: Res<AssistMode>| {
+                    player_collision_distance(
+                        &display_properties,
+                        &hitbox_scale,
+                        &bullet_growth,
+                        &assist_mode,
+                    )
+                },
+            )
+            .unwrap();
+        let distances = &world.resource::<FreshBulletDistances>().values;
+        assert!(!distances.is_empty());
+        for distance in distances {
+            assert!(*distance >= collision_distance);
+        }
+    }
+
+    #[test]
+    fn cursor_visibility_follows_the_aim_mode() {
+        let mut app = headless_app();
+        let cursor_visible = |app: &mut App| {
+            let world = app.world_mut();
+            world
+                .query::<&bevy::window::CursorOptions>()
+                .single(world)
+                .unwrap()
+                .visible
+        };
+
+        app.world_mut().resource_mut::<AimModeSetting>().value = AimMode::Absolute;
+        start_run(&mut app);
+        assert!(cursor_visible(&mut app));
+
+        set_state(&mut app, AppState::Menu);
+        assert!(cursor_visible(&mut app));
+        app.world_mut().resource_mut::<AimModeSetting>().value = AimMode::Relative;
+        start_run(&mut app);
+        assert!(!cursor_visible(&mut app));
+
+        set_state(&mut app, AppState::Menu);
+        assert!(cursor_visible(&mut app));
+    }
+
+    fn weaving_script() -> Vec<InputState> {
+        (0..256)
+            .map(|step| InputState {
+                movement: match step / 32 % 4 {
+                    0 => Vec2::X,
+                    1 => Vec2::Y,
+                    2 => Vec2::NEG_X,
+                    _ => Vec2::NEG_Y,
+                },
+                focus: step % 64 >= 48,
+                ..default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scripted_runs_play_out_the_same() {
+        let outcome = || {
+            let mut app = headless_app();
+            start_run(&mut app);
+            run_script(&mut app, weaving_script());
+            (
+                player_is_dead(&mut app),
+                bullet_count(&mut app),
+                app.world().resource::<Score>().value,
+                player_position(&mut app),
+            )
+        };
+        let first = outcome();
+        // auto fire only kicks in after the opening delay, a few seconds in there have to be bullets
+        assert!(first.1 > 0);
+        assert!(first.2 > 0.0);
+        assert_eq!(first, outcome());
+    }
+
+    #[test]
+    fn scripted_movement_into_a_bullet_ends_the_run() {
+        let mut app = headless_app();
+        start_run(&mut app);
+        let shorter_dimension = app
+            .world()
+            .resource::<DisplayProperties>()
+            .shorter_dimension;
+        let position = player_position(&mut app) + Vec3::X * shorter_dimension * 0.2;
+        spawn_test_bullet(&mut app, position, Vec3::ZERO);
+
+        let steps = vec![
+            InputState {
+                movement: Vec2::X,
+                ..default()
+            };
+            64
+        ];
+        run_script(&mut app, steps);
+        assert!(player_is_dead(&mut app));
+        assert_eq!(bullet_count(&mut app), 1);
+        // the clock stops on the hit, so the score stays short of the full second that was played
+        let score = app.world().resource::<Score>().value;
+        assert!(score > 0.0 && score < 1.0);
+    }
+
+    // sends a bullet toward the right wall and checks every bullet stays finite and inside the arena
+    fn assert_bullets_bounce_within_arena(app: &mut App) {
+        let display = app.world().resource::<DisplayProperties>();
+        let (half_w, half_h) = (display.half_w, display.half_h);
+        let bullet = spawn_test_bullet(app, Vec3::ZERO, vec3(1.0, 0.3, 0.0));
+        let mut bounced = false;
+        for _ in 0..512 {
+            app.update();
+            let world = app.world_mut();
+            for (entity, transform, bouncer) in world
+                .query_filtered::<(Entity, &Transform, &ScreenEdgeBouncer), With<Bullet>>()
+                .iter(world)
+            {
+                assert!(transform.translation.is_finite() && bouncer.velocity.is_finite());
+                assert!(transform.translation.x.abs() <= half_w + 1e-3);
+                assert!(transform.translation.y.abs() <= half_h + 1e-3);
+                bounced |= entity == bullet && bouncer.velocity.x < 0.0;
+            }
+        }
+        assert!(bounced);
+    }
+
+    #[test]
+    fn bullets_keep_bouncing_in_a_tiny_window() {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<PracticeMode>().value = true;
+        start_run(&mut app);
+
+        resize_window(&mut app, 100, 100);
+        app.update();
+        let display = app.world().resource::<DisplayProperties>();
+        assert!(display.half_w > 0.0 && display.half_h > 0.0);
+        assert_bullets_bounce_within_arena(&mut app);
+    }
+
+    #[test]
+    fn bullet_colors_skip_a_missing_material() {
+        let mut app = headless_app();
+        let material = app
+            .world()
+            .resource::<BulletRenderComponents>()
+            .material
+            .id();
+        app.world_mut()
+            .resource_mut::<Assets<ColorMaterial>>()
+            .remove(material);
+        let material_count = app.world().resource::<Assets<ColorMaterial>>().len();
+
+        app.world_mut()
+            .run_system_once(oscilate_bullet_colors)
+            .unwrap();
+        let materials = app.world().resource::<Assets<ColorMaterial>>();
+        assert!(!materials.contains(material));
+        assert_eq!(materials.len(), material_count);
+    }
+
+    // the pause checks above run on every one of these updates, including the one after the resize
+    #[test]
+    fn a_resize_while_paused_moves_nothing_on_its_own() {
+        let mut app = headless_app();
+        start_run(&mut app);
+        spawn_test_bullet(&mut app, vec3(100.0, 50.0, 0.0), vec3(1.0, -0.5, 0.0));
+        tap_key(&mut app, KeyCode::Escape);
+        app.update();
+        assert_eq!(current_state(&app), AppState::Paused);
+        let score = app.world().resource::<Score>().value;
+        for _ in 0..16 {
+            app.update();
+        }
+
+        resize_window(&mut app, 800, 600);
+        for _ in 0..16 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<DisplayProperties>().w, 800.0);
+        assert_eq!(app.world().resource::<Score>().value, score);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "moved while paused")]
+    fn a_bullet_moving_while_paused_trips_the_pause_check() {
+        let mut app = headless_app();
+        start_run(&mut app);
+        let bullet = spawn_test_bullet(&mut app, vec3(100.0, 50.0, 0.0), vec3(1.0, -0.5, 0.0));
+        tap_key(&mut app, KeyCode::Escape);
+        app.update();
+        assert_eq!(current_state(&app), AppState::Paused);
+
+        // stands in for an Update system moving it on Time<Real>
+        app.world_mut()
+            .get_mut::<Transform>(bullet)
+            .unwrap()
+            .translation
+            .x += 1.0;
+        app.update();
+    }
+
+    #[test]
+    fn a_three_pellet_spread_fans_out_across_the_angle() {
+        let pattern = ShotPattern::Spread {
+            count: 3,
+            angle: 30.0,
+        };
+        let expected = [-15.0_f32, 0.0, 15.0].map(f32::to_radians);
+        for (angle, expected) in pattern.pellet_angles().iter().zip(expected) {
+            assert!((angle - expected).abs() < 1e-5);
+        }
+
+        let mut app = headless_app();
+        app.insert_resource(pattern);
+        start_run(&mut app);
+        for _ in 0..256 {
+            if bullet_count(&mut app) > 0 {
+                break;
+            }
+            app.update();
+        }
+        assert_eq!(bullet_count(&mut app), 3);
+
+        let world = app.world_mut();
+        let aim = world
+            .query_filtered::<&Transform, With<PlayerAim>>()
+            .single(world)
+            .unwrap()
+            .translation;
+        let player = player_position(&mut app);
+        let aim_direction = shot_direction(player, aim).truncate();
+        let world = app.world_mut();
+        let mut angles: Vec<f32> = world
+            .query_filtered::<&ScreenEdgeBouncer, With<Bullet>>()
+            .iter(world)
+            .map(|bouncer| aim_direction.angle_to(bouncer.velocity.truncate()))
+            .collect();
+        angles.sort_by(f32::total_cmp);
+        // every pellet may stray by the jitter, a fraction of the gap to its neighbours
+        let jitter = 15.0_f32.to_radians() * SHOT_SPREAD_JITTER;
+        for (angle, expected) in angles.iter().zip(expected) {
+            assert!((angle - expected).abs() <= jitter + 1e-4);
+        }
+    }
+
+    #[test]
+    fn focus_halves_the_distance_moved_per_step() {
+        let mut app = headless_app();
+        start_run(&mut app);
+        let step_distance = |app: &mut App, focus: bool| {
+            let before = player_position(app);
+            run_script(
+                app,
+                vec![InputState {
+                    movement: Vec2::X,
+                    focus,
+                    ..default()
+                }],
+            );
+            player_position(app).x - before.x
+        };
+        let regular = step_distance(&mut app, false);
+        let focused = step_distance(&mut app, true);
+        assert!(regular > 0.0);
+        assert!((focused / regular - FOCUS_SPEED_MULTIPLIER).abs() < 1e-2);
+    }
+
+    #[test]
+    fn no_fire_key_option_clashes_with_focus() {
+        let mut world = World::new();
+        GameConfig::default().insert_resources(&mut world);
+        let focus_key = world.resource::<KeyBindings>().focus_key;
+        for option in [FireKey::Space, FireKey::KeyF, FireKey::KeyX] {
+            // stops compiling once an option is added without being listed above
+            match option {
+                FireKey::Space | FireKey::KeyF | FireKey::KeyX => {}
+            }
+            assert_ne!(KeyCode::from(option), focus_key);
+        }
+        // configs saved while shift was still a fire key come back on the default
+        assert_eq!(
+            ron::from_str::<FireKey>("ShiftLeft").unwrap(),
+            FireKey::Space
+        );
+    }
+
+    #[test]
+    fn the_gamepad_pause_button_toggles_pause() {
+        let mut app = headless_app();
+        let gamepad = app.world_mut().spawn(Gamepad::default()).id();
+        start_run(&mut app);
+        let pause_button = app.world().resource::<KeyBindings>().pause_gamepad;
+
+        tap_gamepad_button(&mut app, gamepad, pause_button);
+        app.update();
+        assert_eq!(current_state(&app), AppState::Paused);
+        assert_eq!(
+            app.world().resource::<PrimaryControlDevice>().value,
+            ControlDevice::Gamepad
+        );
+
+        // past the cooldown that keeps one press from toggling twice
+        for _ in 0..32 {
+            app.update();
+        }
+        tap_gamepad_button(&mut app, gamepad, pause_button);
+        app.update();
+        assert_eq!(current_state(&app), AppState::InGame);
+        assert!(!app.world().resource::<Time<Virtual>>().is_paused());
+    }
+
+    #[test]
+    fn bullets_bounce_within_a_reduced_arena() {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<PracticeMode>().value = true;
+        app.world_mut().resource_mut::<ArenaFraction>().value = 0.5;
+        start_run(&mut app);
+
+        let display = app.world().resource::<DisplayProperties>();
+        assert!((display.half_w - display.w / 4.0).abs() < 1e-3);
+        assert!((display.half_h - display.h / 4.0).abs() < 1e-3);
+        assert_bullets_bounce_within_arena(&mut app);
+    }
+
+    #[test]
+    fn a_revive_saves_the_run_only_once() {
+        let mut app = headless_app();
+        app.init_resource::<DeathCount>();
+        app.add_systems(Update, count_deaths);
+        app.world_mut().resource_mut::<ReviveEnabled>().value = true;
+        start_run(&mut app);
+        assert_eq!(app.world().resource::<Recovery>().revives, 1);
+
+        // a bullet that doesn't move stays on the player after the revive pushes the crowd away
+        let position = player_position(&mut app);
+        spawn_test_bullet(&mut app, position, Vec3::ZERO);
+        for _ in 0..8 {
+            app.update();
+        }
+        assert_eq!(current_state(&app), AppState::InGame);
+        assert_eq!(app.world().resource::<Recovery>().revives, 0);
+        assert_eq!(app.world().resource::<DeathCount>().value, 0);
+        assert!(!player_is_dead(&mut app));
+
+        // once the recovery window runs out, the same bullet ends the run
+        for _ in 0..256 {
+            app.update();
+        }
+        assert_eq!(current_state(&app), AppState::GameOver);
+        assert_eq!(app.world().resource::<DeathCount>().value, 1);
+    }
+
+    #[test]
+    fn losing_the_active_gamepad_pauses_the_run() {
+        let mut app = headless_app();
+        let gamepad = app.world_mut().spawn(Gamepad::default()).id();
+        start_run(&mut app);
+
+        // a press makes it the active gamepad, holding focus through a fixed step makes it the primary device
+        let focus_button = app.world().resource::<KeyBindings>().focus_gamepad;
+        tap_gamepad_button(&mut app, gamepad, focus_button);
+        assert_eq!(app.world().resource::<ActiveGamepad>().value, Some(gamepad));
+        assert_eq!(
+            app.world().resource::<PrimaryControlDevice>().value,
+            ControlDevice::Gamepad
+        );
+
+        app.world_mut().despawn(gamepad);
+        app.update();
+        app.update();
+        assert_eq!(current_state(&app), AppState::Paused);
+        assert!(app.world().resource::<ControllerDisconnected>().value);
+        assert!(app.world().resource::<Time<Virtual>>().is_paused());
+    }
+
+    #[test]
+    fn leaving_a_run_leaves_no_gameplay_behind() {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<WavesEnabled>().value = true;
+        app.world_mut().resource_mut::<HeatmapEnabled>().value = true;
+        app.world_mut()
+            .resource_mut::<ThreatIndicatorsEnabled>()
+            .value = true;
+        start_run(&mut app);
+        run_script(&mut app, weaving_script());
+        assert!(bullet_count(&mut app) > 0);
+
+        set_state(&mut app, AppState::Menu);
+        app.update();
+        let world = app.world_mut();
+        let leftovers = world
+            .query_filtered::<(), GameplayLeftover>()
+            .iter(world)
+            .count();
+        assert_eq!(leftovers, 0);
+        let batch = world.resource::<ParticleBatch>();
+        assert!(batch.trail.is_empty() && batch.bounce.is_empty() && batch.ripple.is_empty());
+    }
+
+    #[test]
+    fn an_overlapping_pair_scores_a_single_bounce() {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<SelectedGameMode>().value = GameMode::ScoreAttack;
+        spawn_test_bullet(&mut app, vec3(-5.0, 0.0, 0.0), Vec3::X);
+        spawn_test_bullet(&mut app, vec3(5.0, 0.0, 0.0), Vec3::NEG_X);
+
+        // the bullets never move between these, so they stay overlapping the whole time
+        for _ in 0..4 {
+            app.world_mut()
+                .run_system_once(handle_bullet_collision)
+                .unwrap();
+        }
+        assert_eq!(
+            app.world().resource::<ScoreAttack>().points,
+            SCORE_ATTACK_POINTS_PER_BOUNCE * 2.0
+        );
+    }
+
+    // the benchmarks are ignored by default, run them with cargo test --release -- --ignored --nocapture
+    fn bench(name: &str, mut round: impl FnMut()) -> Duration {
+        for _ in 0..BENCH_WARMUP_ROUNDS {
+            round();
+        }
+        let start = Instant::now();
+        for _ in 0..BENCH_ROUNDS {
+            round();
+        }
+        let per_round = start.elapsed() / BENCH_ROUNDS;
+        println!("{}: {:?} per round", name, per_round);
+        per_round
+    }
+
+    const BENCH_PARTICLES: usize = 2000;
+    // outlives every round, so both layouts keep the full particle count throughout
+    const BENCH_PARTICLE_LIFETIME: f32 = 3600.0;
+
+    // the entity per particle layout the batches replaced, kept only to time against
+    #[derive(Component)]
+    struct EntityTrailParticle {
+        lifetime: f32,
+        max_lifetime: f32,
+        base_color: Color,
+    }
+
+    #[derive(Component)]
+    struct EntityBounceParticle {
+        lifetime: f32,
+        velocity: Vec3,
+    }
+
+    fn bench_particle_position(i: usize) -> Vec2 {
+        vec2(
+            (i % 40) as f32 * 10.0 - 200.0,
+            (i / 40) as f32 * 10.0 - 125.0,
+        )
+    }
+
+    fn spawn_entity_particles(
+        mut commands: Commands,
+        bullet_data: Res<BulletRenderComponents>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        for i in 0..BENCH_PARTICLES / 2 {
+            let position = bench_particle_position(i);
+            commands.spawn((
+                EntityTrailParticle {
+                    lifetime: BENCH_PARTICLE_LIFETIME,
+                    max_lifetime: BENCH_PARTICLE_LIFETIME,
+                    base_color: Color::WHITE,
+                },
+                Mesh2d(bullet_data.mesh.clone()),
+                MeshMaterial2d(materials.add(ColorMaterial {
+                    color: Color::WHITE,
+                    alpha_mode: AlphaMode2d::Blend,
+                    ..default()
+                })),
+                Transform::from_translation(position.extend(TRAIL_LAYER)),
+            ));
+            commands.spawn((
+                EntityBounceParticle {
+                    lifetime: BENCH_PARTICLE_LIFETIME,
+                    velocity: Vec3::X,
+                },
+                Transform::from_translation(position.extend(BOUNCE_PARTICLE_LAYER)),
+                Mesh2d(bullet_data.mesh.clone()),
+                MeshMaterial2d(bullet_data.material.clone()),
+            ));
+        }
+    }
+
+    fn age_entity_trail_particles(
+        mut commands: Commands,
+        particles: Query<(
+            Entity,
+            &mut Transform,
+            &mut EntityTrailParticle,
+            &MeshMaterial2d<ColorMaterial>,
+        )>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        time: Res<Time<Virtual>>,
+    ) {
+        for (entity, mut transform, mut particle, material) in particles {
+            particle.lifetime -= time.delta_secs();
+            if particle.lifetime < 0.0 {
+                commands.entity(entity).despawn();
+                continue;
+            }
+
+            let progress = particle.lifetime / particle.max_lifetime;
+            transform.scale = Vec3::ONE * 0.0.lerp(0.5, progress);
+
+            if let Some(mat) = materials.get_mut(material.id()) {
+                mat.color = particle
+                    .base_color
+                    .with_alpha(particle.base_color.alpha() * progress);
+            }
+        }
+    }
+
+    fn age_entity_bounce_particles(
+        mut commands: Commands,
+        particles: Query<(Entity, &mut Transform, &mut EntityBounceParticle)>,
+        time: Res<Time<Virtual>>,
+        display_properties: Res<DisplayProperties>,
+    ) {
+        for (entity, mut transform, mut particle) in particles {
+            particle.lifetime -= time.delta_secs();
+            if particle.lifetime < 0.0 {
+                commands.entity(entity).despawn();
+                continue;
+            }
+
+            transform.scale = Vec3::ONE
+                * ((PI / 2.0).lerp(0.0, particle.lifetime / TRAIL_PARTICLE_LIFETIME)).cos()
+                * 0.5;
+            transform.translation += particle.velocity
+                * ((PI / 2.0).lerp(0.0, particle.lifetime / TRAIL_PARTICLE_LIFETIME)).cos()
+                * COLLISION_PARTICLE_SPEED_NORMALIZED
+                * display_properties.shorter_dimension
+                * time.delta_secs();
+        }
+    }
+
+    // only the main world is timed, the draw call per entity the batches save is on the render side on top of this
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_particle_entities_against_batches() {
+        let mut app = headless_app();
+        app.world_mut()
+            .run_system_once(spawn_entity_particles)
+            .unwrap();
+        let mut per_entity = Schedule::default();
+        per_entity.add_systems((age_entity_trail_particles, age_entity_bounce_particles));
+        let entities = bench("2000 particles, an entity each", || {
+            per_entity.run(app.world_mut())
+        });
+
+        let mut batch = app.world_mut().resource_mut::<ParticleBatch>();
+        for i in 0..BENCH_PARTICLES / 2 {
+            let position = bench_particle_position(i);
+            batch.trail.push(TrailParticle {
+                position,
+                lifetime: BENCH_PARTICLE_LIFETIME,
+                max_lifetime: BENCH_PARTICLE_LIFETIME,
+                base_color: Color::WHITE,
+            });
+            batch.bounce.push(BounceParticle {
+                position,
+                lifetime: BENCH_PARTICLE_LIFETIME,
+                velocity: Vec3::X,
+            });
+        }
+        let mut batched = Schedule::default();
+        batched.add_systems(
+            (
+                handle_trail_particles,
+                handle_bounce_particles,
+                draw_particle_batches,
+            )
+                .chain(),
+        );
+        let batches = bench("2000 particles, one mesh per kind", || {
+            batched.run(app.world_mut())
+        });
+        println!(
+            "the batches take {:.2}x the time of an entity each",
+            batches.as_secs_f64() / entities.as_secs_f64()
+        );
+
+        // a layout that lost particles along the way would have had less to do
+        let world = app.world_mut();
+        let entity_count = world.query::<&EntityTrailParticle>().iter(world).count()
+            + world.query::<&EntityBounceParticle>().iter(world).count();
+        let batch = world.resource::<ParticleBatch>();
+        assert_eq!(entity_count, BENCH_PARTICLES);
+        assert_eq!(batch.trail.len() + batch.bounce.len(), BENCH_PARTICLES);
+    }
+
+    const BENCH_BULLETS: usize = 400;
+
+    // the pair check as it was before squared distances, kept only to time against
+    fn resolve_bullet_pair_by_distance(
+        (first_position, first): (Vec3, &ScreenEdgeBouncer),
+        (second_position, second): (Vec3, &ScreenEdgeBouncer),
+        collision_distance: f32,
+        elasticity: f32,
+    ) -> Option<(Vec3, Vec3, Vec3)> {
+        let distance = first_position.distance(second_position);
+        if distance > collision_distance || distance < 1.0 {
+            return None;
+        }
+
+        let dir = (first_position - second_position).normalize();
+        Some((
+            dir * (first.velocity.length() * elasticity).min(BULLET_MAX_SPEED_MULTIPLIER),
+            -dir * (second.velocity.length() * elasticity).min(BULLET_MAX_SPEED_MULTIPLIER),
+            (first_position + second_position) / 2.0,
+        ))
+    }
+
+    fn bullet_pairs(
+        bullets: &[(Vec3, ScreenEdgeBouncer)],
+    ) -> impl Iterator<Item = (&(Vec3, ScreenEdgeBouncer), &(Vec3, ScreenEdgeBouncer))> {
+        bullets
+            .iter()
+            .enumerate()
+            .flat_map(move |(i, first)| bullets[i + 1..].iter().map(move |second| (first, second)))
+    }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_bullet_pairs_by_squared_distance() {
+        let mut rng = ChaCha8Rng::seed_from_u64(TEST_SEED);
+        let bullets: Vec<(Vec3, ScreenEdgeBouncer)> = (0..BENCH_BULLETS)
+            .map(|_| {
+                (
+                    vec3(
+                        rng.random_range(-640.0..640.0),
+                        rng.random_range(-360.0..360.0),
+                        0.0,
+                    ),
+                    ScreenEdgeBouncer {
+                        velocity: vec3(
+                            rng.random_range(-1.0..1.0),
+                            rng.random_range(-1.0..1.0),
+                            0.0,
+                        ),
+                    },
+                )
+            })
+            .collect();
+        // the same bullet size the game uses on a 720 pixel tall arena
+        let collision_distance = PLAYER_SIZE * 2.0 * 720.0;
+        let collision_distance_squared = collision_distance * collision_distance;
+
+        let by_distance = bench("400 bullets, distance per pair", || {
+            for ((first_position, first), (second_position, second)) in bullet_pairs(&bullets) {
+                black_box(resolve_bullet_pair_by_distance(
+                    (*first_position, first),
+                    (*second_position, second),
+                    collision_distance,
+                    1.0,
+                ));
+            }
+        });
+        let by_squared_distance = bench("400 bullets, squared distance per pair", || {
+            for ((first_position, first), (second_position, second)) in bullet_pairs(&bullets) {
+                black_box(resolve_bullet_pair(
+                    (*first_position, first),
+                    (*second_position, second),
+                    collision_distance_squared,
+                    1.0,
+                ));
+            }
+        });
+        println!(
+            "squared distances take {:.2}x the time of plain ones",
+            by_squared_distance.as_secs_f64() / by_distance.as_secs_f64()
+        );
+
+        // a resolved pair takes three roots either way, the direction and both speeds
+        let pair_count = bullet_pairs(&bullets).count();
+        let resolved_by_distance = bullet_pairs(&bullets)
+            .filter(|((first_position, first), (second_position, second))| {
+                resolve_bullet_pair_by_distance(
+                    (*first_position, first),
+                    (*second_position, second),
+                    collision_distance,
+                    1.0,
+                )
+                .is_some()
+            })
+            .count();
+        let resolved_by_squared_distance = bullet_pairs(&bullets)
+            .filter(|((first_position, first), (second_position, second))| {
+                resolve_bullet_pair(
+                    (*first_position, first),
+                    (*second_position, second),
+                    collision_distance_squared,
+                    1.0,
+                )
+                .is_some()
+            })
+            .count();
+        let roots_by_distance = pair_count + resolved_by_distance * 3;
+        let roots_by_squared_distance = resolved_by_squared_distance * 3;
+        println!(
+            "{} pairs per pass, square roots went from {} to {}",
+            pair_count, roots_by_distance, roots_by_squared_distance
+        );
+        assert!(roots_by_squared_distance < roots_by_distance);
+    }
+}